@@ -0,0 +1,345 @@
+use crate::ai::Sampling;
+use crate::config::ActionsConfig;
+use crate::monitors::state::{Mood, MoodOverride};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+
+#[cfg(test)]
+#[path = "actions_test.rs"]
+mod actions_test;
+
+/// Every action type, as named in JSON and in `actions.allowed`.
+pub const ACTION_TYPES: &[&str] = &["say", "emote", "set_mood", "set_reminder", "open_url"];
+
+/// Animations the frontend can play for `emote`.
+pub const EMOTES: &[&str] = &["wave", "bounce", "spin", "nod", "shake", "sparkle"];
+
+const MAX_REMINDER_MINUTES: u32 = 24 * 60;
+const MAX_TEXT_CHARS: usize = 500;
+
+/// Tokens allowed for the `{"actions": [...]}` wrapper on top of the reply's
+/// own budget.
+pub const WRAPPER_TOKENS: u32 = 128;
+
+/// Shown when a broken reply carries no text at all.
+const NOTHING_SAID: &str = "…";
+
+/// Fields whose string values are meant for the speech bubble.
+const SAY_KEYS: &[&str] = &["text", "say", "reply", "message"];
+
+/// A quoted JSON string, or one cut off at the end of the reply. The second
+/// group is set when the string is an object key.
+static JSON_STRING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#""((?:[^"\\]|\\.)*)(?:"(\s*:)?|\\?$)"#).unwrap());
+
+/// Something the model asks the sprite to do.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SpriteAction {
+    /// Text for the speech bubble.
+    Say { text: String },
+    /// Play one of the [`EMOTES`].
+    Emote { emote: String },
+    /// Override the computed mood for `actions.mood_minutes`.
+    SetMood { mood: String },
+    /// Send a notification after `minutes`.
+    SetReminder { minutes: u32, text: String },
+    /// Open an http(s) link in the default browser.
+    OpenUrl { url: String },
+}
+
+impl SpriteAction {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SpriteAction::Say { .. } => "say",
+            SpriteAction::Emote { .. } => "emote",
+            SpriteAction::SetMood { .. } => "set_mood",
+            SpriteAction::SetReminder { .. } => "set_reminder",
+            SpriteAction::OpenUrl { .. } => "open_url",
+        }
+    }
+
+    /// Check the fields the schema can't express.
+    pub fn validate(&self) -> Result<(), ActionError> {
+        match self {
+            SpriteAction::Say { text } => check_text(text),
+            SpriteAction::Emote { emote } => {
+                if EMOTES.contains(&emote.as_str()) {
+                    Ok(())
+                } else {
+                    Err(ActionError::Invalid(format!("unknown emote {:?}", emote)))
+                }
+            }
+            SpriteAction::SetMood { mood } => Mood::from_name(mood)
+                .map(|_| ())
+                .ok_or_else(|| ActionError::Invalid(format!("unknown mood {:?}", mood))),
+            SpriteAction::SetReminder { minutes, text } => {
+                if !(1..=MAX_REMINDER_MINUTES).contains(minutes) {
+                    return Err(ActionError::Invalid(format!(
+                        "reminder in {} minutes is out of range",
+                        minutes
+                    )));
+                }
+                check_text(text)
+            }
+            SpriteAction::OpenUrl { url } => match reqwest::Url::parse(url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+                _ => Err(ActionError::Invalid(format!(
+                    "not an http(s) URL: {:?}",
+                    url
+                ))),
+            },
+        }
+    }
+}
+
+fn check_text(text: &str) -> Result<(), ActionError> {
+    let chars = text.trim().chars().count();
+    if chars == 0 || chars > MAX_TEXT_CHARS {
+        return Err(ActionError::Invalid(format!(
+            "text must be 1 to {} characters",
+            MAX_TEXT_CHARS
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ActionError {
+    #[error("action does not match the schema: {0}")]
+    Schema(String),
+    #[error("invalid action: {0}")]
+    Invalid(String),
+    #[error("action not allowed: {0}")]
+    NotAllowed(String),
+}
+
+/// What to do with a model reply: the text to show and the other actions to
+/// run. Actions that failed validation or aren't allowed end up in `rejected`.
+#[derive(Debug, Default, PartialEq)]
+pub struct ActionPlan {
+    pub say: String,
+    pub actions: Vec<SpriteAction>,
+    pub rejected: Vec<ActionError>,
+}
+
+/// Instructions appended to the conversation when structured replies are on.
+pub fn format_instructions(config: &ActionsConfig) -> String {
+    let mut lines = vec![
+        "Reply ONLY with a JSON object of the form {\"actions\": [...]}, no other text. \
+         Each action is an object with a \"type\". Available actions:"
+            .to_string(),
+        "- {\"type\": \"say\", \"text\": \"...\"}: what you say to the user.".to_string(),
+    ];
+    for kind in &config.allowed {
+        let line = match kind.as_str() {
+            "emote" => format!(
+                "- {{\"type\": \"emote\", \"emote\": \"...\"}}: play an animation, one of {}.",
+                EMOTES.join(", ")
+            ),
+            "set_mood" => format!(
+                "- {{\"type\": \"set_mood\", \"mood\": \"...\"}}: change your mood, one of {}.",
+                Mood::ALL
+                    .iter()
+                    .map(|m| format!("{:?}", m).to_lowercase())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            "set_reminder" => format!(
+                "- {{\"type\": \"set_reminder\", \"minutes\": N, \"text\": \"...\"}}: remind the \
+                 user in N minutes (1 to {}). Only when the user asks.",
+                MAX_REMINDER_MINUTES
+            ),
+            "open_url" => "- {\"type\": \"open_url\", \"url\": \"https://...\"}: open a web page. \
+                           Only when the user asks."
+                .to_string(),
+            _ => continue,
+        };
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// `sampling` with room for the JSON wrapper, so a reply of the usual length
+/// isn't cut off in the middle of it.
+pub fn wrapper_sampling(sampling: Sampling) -> Sampling {
+    Sampling {
+        max_tokens: sampling
+            .max_tokens
+            .map(|tokens| tokens.saturating_add(WRAPPER_TOKENS)),
+        ..sampling
+    }
+}
+
+/// Turn a model reply into an [`ActionPlan`]. A reply that isn't an action
+/// object (or a list of them) is shown as plain text; one that is JSON but
+/// can't be used (cut off, say) only ever shows the text it carries.
+pub fn plan(reply: &str, config: &ActionsConfig) -> ActionPlan {
+    let Some(values) = parse_actions(reply) else {
+        let say = if looks_like_json(reply) {
+            salvage_say(reply)
+        } else {
+            reply.trim().to_string()
+        };
+        return ActionPlan {
+            say,
+            ..ActionPlan::default()
+        };
+    };
+
+    let mut plan = ActionPlan::default();
+    let mut said = Vec::new();
+    for value in values {
+        let action = match serde_json::from_value::<SpriteAction>(value) {
+            Ok(action) => action,
+            Err(e) => {
+                plan.rejected.push(ActionError::Schema(e.to_string()));
+                continue;
+            }
+        };
+        if let Err(e) = action.validate() {
+            plan.rejected.push(e);
+            continue;
+        }
+        match action {
+            SpriteAction::Say { text } => said.push(text.trim().to_string()),
+            action if config.allowed.iter().any(|a| a == action.kind()) => {
+                plan.actions.push(action)
+            }
+            action => plan
+                .rejected
+                .push(ActionError::NotAllowed(action.kind().to_string())),
+        }
+    }
+
+    plan.say = said.join(" ");
+    if plan.say.is_empty() && plan.actions.is_empty() {
+        // Nothing usable; keep whatever text the reply carries.
+        plan.say = salvage_say(reply);
+    } else if plan.say.is_empty() {
+        // Give the bubble something to show for a silent emote.
+        if let Some(SpriteAction::Emote { emote }) = plan
+            .actions
+            .iter()
+            .find(|a| matches!(a, SpriteAction::Emote { .. }))
+        {
+            plan.say = format!("*{}*", emote);
+        }
+    }
+    plan
+}
+
+/// The action list in `reply`: `{"actions": [...]}`, a bare list or a single
+/// action, optionally inside a code fence or surrounded by chatter.
+fn parse_actions(reply: &str) -> Option<Vec<serde_json::Value>> {
+    let start = reply.find(['{', '['])?;
+    let end = reply.rfind(['}', ']'])?;
+    if end < start {
+        return None;
+    }
+    match serde_json::from_str::<serde_json::Value>(&reply[start..=end]).ok()? {
+        serde_json::Value::Object(mut object) => match object.remove("actions") {
+            Some(serde_json::Value::Array(actions)) => Some(actions),
+            Some(_) => None,
+            None if object.contains_key("type") => Some(vec![object.into()]),
+            None => None,
+        },
+        serde_json::Value::Array(actions) => Some(actions),
+        _ => None,
+    }
+}
+
+/// Whether `reply` has JSON object keys in it, as opposed to prose that
+/// merely uses braces.
+fn looks_like_json(reply: &str) -> bool {
+    JSON_STRING
+        .captures_iter(reply)
+        .any(|caps| caps.get(2).is_some())
+}
+
+/// The text of the `say`-like fields in a JSON reply that couldn't be
+/// parsed, including one cut off mid-string. Objects of another `type` (a
+/// reminder's text, say) are skipped.
+fn salvage_say(reply: &str) -> String {
+    let mut said = Vec::new();
+    for object in reply.split('{') {
+        let mut kind = None;
+        let mut key = None;
+        let mut texts = Vec::new();
+        for caps in JSON_STRING.captures_iter(object) {
+            let value = unescape(&caps[1]);
+            if caps.get(2).is_some() {
+                key = Some(value);
+                continue;
+            }
+            match key.take().as_deref() {
+                Some("type") => kind = Some(value),
+                Some(key) if SAY_KEYS.contains(&key) => texts.push(value),
+                _ => {}
+            }
+        }
+        if kind.as_deref().is_none_or(|kind| kind == "say") {
+            said.extend(texts.iter().map(|text| text.trim().to_string()));
+        }
+    }
+    said.retain(|text| !text.is_empty());
+    if said.is_empty() {
+        NOTHING_SAID.to_string()
+    } else {
+        said.join(" ")
+    }
+}
+
+fn unescape(raw: &str) -> String {
+    serde_json::from_str(&format!("\"{}\"", raw)).unwrap_or_else(|_| raw.replace("\\\"", "\""))
+}
+
+/// Payload of the `sprite-emote` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpriteEmote {
+    pub emote: String,
+}
+
+/// Run the non-`say` actions of a plan.
+pub fn dispatch(app: &AppHandle, actions: Vec<SpriteAction>, config: &ActionsConfig) {
+    for action in actions {
+        tracing::info!("Running sprite action {}", action.kind());
+        match action {
+            SpriteAction::Say { .. } => {}
+            SpriteAction::Emote { emote } => {
+                if let Err(e) = app.emit("sprite-emote", SpriteEmote { emote }) {
+                    tracing::error!("Failed to emit sprite-emote: {}", e);
+                }
+            }
+            SpriteAction::SetMood { mood } => {
+                if let (Some(mood), Some(slot)) =
+                    (Mood::from_name(&mood), app.try_state::<MoodOverride>())
+                {
+                    let duration = Duration::from_secs(config.mood_minutes * 60);
+                    slot.set(mood, duration, Instant::now());
+                }
+            }
+            SpriteAction::SetReminder { minutes, text } => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    tokio::time::sleep(Duration::from_secs(u64::from(minutes) * 60)).await;
+                    crate::utils::notification::send_notification(
+                        &app,
+                        "Ethereal: Reminder",
+                        &text,
+                    );
+                });
+            }
+            SpriteAction::OpenUrl { url } => {
+                use tauri_plugin_opener::OpenerExt;
+                if let Err(e) = app.opener().open_url(url, None::<&str>) {
+                    tracing::error!("Failed to open URL: {}", e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::actions::{
+        format_instructions, plan, wrapper_sampling, ActionError, SpriteAction, WRAPPER_TOKENS,
+    };
+    use crate::ai::Sampling;
+    use crate::config::{ActionsConfig, AppConfig};
+
+    fn all_allowed() -> ActionsConfig {
+        ActionsConfig {
+            enabled: true,
+            allowed: vec![
+                "emote".to_string(),
+                "set_mood".to_string(),
+                "set_reminder".to_string(),
+                "open_url".to_string(),
+            ],
+            ..ActionsConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_splits_say_from_actions() {
+        let reply = r#"{"actions": [
+            {"type": "say", "text": "Break time!"},
+            {"type": "emote", "emote": "wave"},
+            {"type": "set_reminder", "minutes": 15, "text": "Stretch"},
+            {"type": "say", "text": "I'll ping you."}
+        ]}"#;
+        let plan = plan(reply, &all_allowed());
+        assert_eq!(plan.say, "Break time! I'll ping you.");
+        assert_eq!(
+            plan.actions,
+            vec![
+                SpriteAction::Emote {
+                    emote: "wave".to_string()
+                },
+                SpriteAction::SetReminder {
+                    minutes: 15,
+                    text: "Stretch".to_string()
+                },
+            ]
+        );
+        assert!(plan.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_plan_accepts_fenced_and_single_actions() {
+        let fenced = "```json\n{\"actions\": [{\"type\": \"say\", \"text\": \"Hi\"}]}\n```";
+        assert_eq!(plan(fenced, &all_allowed()).say, "Hi");
+
+        let single = r#"Sure: {"type": "set_mood", "mood": "Excited"}"#;
+        let plan = plan(single, &all_allowed());
+        assert_eq!(
+            plan.actions,
+            vec![SpriteAction::SetMood {
+                mood: "Excited".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_plan_falls_back_to_plain_text() {
+        for reply in ["Just a normal reply.", "Use a {placeholder} here."] {
+            let plan = plan(reply, &all_allowed());
+            assert_eq!(plan.say, reply);
+            assert!(plan.actions.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_plan_never_shows_unusable_json() {
+        for (reply, say) in [
+            (r#"{"actions": "none"}"#, "…"),
+            (r#"{"reply": "hi"}"#, "hi"),
+            (r#"[{"type": "emote", "emote": "explode"}]"#, "…"),
+        ] {
+            let plan = plan(reply, &all_allowed());
+            assert_eq!(plan.say, say, "{}", reply);
+            assert!(plan.actions.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_plan_salvages_a_truncated_wrapper() {
+        // Cut off by the token budget in the middle of the text.
+        let reply = r#"{"actions": [{"type": "set_reminder", "minutes": 5, "text": "Tea"}, {"type": "say", "text": "Your \"tea\" is brewing, so"#;
+        let salvaged = plan(reply, &all_allowed());
+        assert_eq!(salvaged.say, r#"Your "tea" is brewing, so"#);
+        assert!(salvaged.actions.is_empty());
+
+        // Cut off before any text: still no JSON in the bubble.
+        let reply = "```json\n{\"actions\": [{\"type\": \"emote\", \"emo";
+        assert_eq!(plan(reply, &all_allowed()).say, "…");
+    }
+
+    #[test]
+    fn test_wrapper_sampling_adds_headroom() {
+        let sampling = Sampling {
+            max_tokens: Some(24),
+            temperature: Some(0.5),
+            ..Sampling::default()
+        };
+        let widened = wrapper_sampling(sampling);
+        assert_eq!(widened.max_tokens, Some(24 + WRAPPER_TOKENS));
+        assert_eq!(widened.temperature, Some(0.5));
+        // Unlimited stays unlimited.
+        assert_eq!(wrapper_sampling(Sampling::default()).max_tokens, None);
+    }
+
+    #[test]
+    fn test_plan_rejects_invalid_actions() {
+        let reply = r#"[
+            {"type": "emote", "emote": "explode"},
+            {"type": "set_mood", "mood": "grumpy"},
+            {"type": "set_reminder", "minutes": 0, "text": "now"},
+            {"type": "set_reminder", "minutes": 5, "text": "  "},
+            {"type": "open_url", "url": "file:///etc/passwd"},
+            {"type": "run_shell", "command": "rm -rf /"},
+            {"type": "say", "text": "Hi", "extra": true},
+            {"type": "say", "text": "Still here"}
+        ]"#;
+        let plan = plan(reply, &all_allowed());
+        assert_eq!(plan.say, "Still here");
+        assert!(plan.actions.is_empty());
+        assert_eq!(plan.rejected.len(), 7);
+        assert!(matches!(plan.rejected[4], ActionError::Invalid(_)));
+        assert!(matches!(plan.rejected[5], ActionError::Schema(_)));
+        assert!(matches!(plan.rejected[6], ActionError::Schema(_)));
+    }
+
+    #[test]
+    fn test_plan_enforces_allowlist() {
+        let reply = r#"{"actions": [
+            {"type": "open_url", "url": "https://example.com"},
+            {"type": "emote", "emote": "spin"}
+        ]}"#;
+        let plan = plan(reply, &ActionsConfig::default());
+        assert_eq!(
+            plan.rejected,
+            vec![ActionError::NotAllowed("open_url".to_string())]
+        );
+        // A silent emote still gives the bubble something to show.
+        assert_eq!(plan.say, "*spin*");
+    }
+
+    #[test]
+    fn test_format_instructions_list_allowed_actions() {
+        let instructions = format_instructions(&ActionsConfig::default());
+        assert!(instructions.contains("\"say\""));
+        assert!(instructions.contains("\"set_mood\""));
+        assert!(instructions.contains("sleeping"));
+        assert!(!instructions.contains("open_url"));
+        assert!(format_instructions(&all_allowed()).contains("open_url"));
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_allowed_action() {
+        let mut config = AppConfig::default();
+        assert!(config.validate().is_ok());
+        config.actions.allowed.push("run_shell".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("run_shell"), "{}", err);
+    }
+}
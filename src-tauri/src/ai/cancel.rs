@@ -0,0 +1,84 @@
+use crate::ai::{AiError, AiResult};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+#[cfg(test)]
+#[path = "cancel_test.rs"]
+mod cancel_test;
+
+/// In-flight AI operations (chat requests, model pulls) the frontend can
+/// abort by key.
+#[derive(Default)]
+pub struct CancelRegistry {
+    tokens: Mutex<HashMap<String, Arc<Notify>>>,
+}
+
+impl CancelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an operation under `key`, refusing a second concurrent one.
+    /// The key is released when the returned registration is dropped.
+    pub fn start(&self, key: &str) -> AiResult<Registration<'_>> {
+        let mut tokens = self.tokens.lock().unwrap();
+        if tokens.contains_key(key) {
+            return Err(AiError::other(format!("{} is already running", key)));
+        }
+        let token = Arc::new(Notify::new());
+        tokens.insert(key.to_string(), token.clone());
+        Ok(Registration {
+            registry: self,
+            key: key.to_string(),
+            token,
+        })
+    }
+
+    /// Ask a running operation to stop. Returns false if none was running.
+    pub fn cancel(&self, key: &str) -> bool {
+        match self.tokens.lock().unwrap().get(key) {
+            Some(token) => {
+                // Stores a permit, so an operation between awaits still sees it.
+                token.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub struct Registration<'a> {
+    registry: &'a CancelRegistry,
+    key: String,
+    token: Arc<Notify>,
+}
+
+impl Registration<'_> {
+    pub fn token(&self) -> &Notify {
+        &self.token
+    }
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        self.registry.tokens.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Await `future` unless `token` fires first, in which case the future is
+/// dropped (aborting any request it has in flight).
+pub async fn cancellable<T, F>(token: Option<&Notify>, future: F) -> AiResult<T>
+where
+    F: Future<Output = AiResult<T>>,
+{
+    match token {
+        Some(token) => tokio::select! {
+            biased;
+            _ = token.notified() => Err(AiError::Cancelled),
+            result = future => result,
+        },
+        None => future.await,
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::cancel::{cancellable, CancelRegistry};
+    use crate::ai::{AiError, AiResult, ChatMessage, ChatProvider, OllamaClient};
+    use crate::config::AiConfig;
+    use std::time::Duration;
+
+    #[test]
+    fn test_registration_refuses_duplicates_until_dropped() {
+        let registry = CancelRegistry::new();
+        assert!(!registry.cancel("chat:1"));
+
+        let first = registry.start("chat:1").unwrap();
+        assert!(registry.start("chat:1").is_err());
+        assert!(registry.start("chat:2").is_ok());
+        assert!(registry.cancel("chat:1"));
+
+        drop(first);
+        assert!(!registry.cancel("chat:1"));
+        assert!(registry.start("chat:1").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cancellable_without_token_just_awaits() {
+        let result: AiResult<u8> = cancellable(None, async { Ok(7) }).await;
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_in_flight_chat() {
+        // Accepts connections (via the backlog) but never answers.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = OllamaClient::new(AiConfig {
+            api_endpoint: format!("http://{}", listener.local_addr().unwrap()),
+            ..AiConfig::default()
+        });
+        let messages = vec![ChatMessage {
+            role: "user".to_string(),
+            content: "hi".to_string(),
+        }];
+
+        let registry = CancelRegistry::new();
+        let registration = registry.start("chat:slow").unwrap();
+        let canceller = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert!(registry.cancel("chat:slow"));
+        };
+
+        let (result, _) = tokio::join!(
+            cancellable(Some(registration.token()), client.chat(messages, None)),
+            canceller
+        );
+        assert!(matches!(result, Err(AiError::Cancelled)));
+    }
+}
@@ -0,0 +1,310 @@
+use crate::ai::{limits::trim_reply, redact::fence, AiResult, ChatMessage, ChatProvider};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+#[path = "clipboard_test.rs"]
+mod clipboard_test;
+
+/// Prose shorter than this isn't worth summarizing.
+const SUMMARY_MIN_CHARS: usize = 200;
+
+/// What a piece of copied text looks like.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClipKind {
+    StackTrace,
+    CompilerError,
+    Url,
+    Code,
+    /// Anything else; `language` is a best guess, `None` when unsure.
+    Prose {
+        language: Option<String>,
+    },
+}
+
+/// One-click actions offered for a clip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipAction {
+    Explain,
+    Summarize,
+    Translate,
+}
+
+/// Payload of the `clipboard-offer` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardOffer {
+    pub text: String,
+    #[serde(flatten)]
+    pub kind: ClipKind,
+    pub actions: Vec<ClipAction>,
+}
+
+impl ClipboardOffer {
+    pub fn new(text: String, translate_to: &str) -> Self {
+        let kind = classify(&text);
+        let actions = actions_for(&kind, &text, translate_to);
+        Self {
+            text,
+            kind,
+            actions,
+        }
+    }
+}
+
+static URL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?i)(https?|ftp)://\S+$|^www\.\S+\.\S+$").unwrap());
+
+/// Markers that settle it on their own.
+static TRACE_HEADER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?m)Traceback \(most recent call last\)|thread '[^']*' panicked at|^stack backtrace:|^goroutine \d+ \[|Exception in thread ""#,
+    )
+    .unwrap()
+});
+
+/// Single frames; it takes two to make a trace.
+static TRACE_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^\s+at .+[:(]\d+(:\d+)?\)?\s*$|^\s+File ".+", line \d+"#).unwrap()
+});
+
+static COMPILER_ERROR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)^error(\[E\d{4}\])?: |^\s*--> \S+:\d+:\d+|^\S+\.\w+:\d+(:\d+)?:\s*(fatal )?error\b|\berror TS\d+:|^\S+\.\w+\(\d+,\d+\): error ",
+    )
+    .unwrap()
+});
+
+static CODE_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(fn|pub|let|const|var|def|class|import|from|#include|function|return|if|for|while|struct|impl|use|package|public|private|async|await|export|interface|type|SELECT|INSERT|UPDATE)\b|[;{}]\s*$|^\s*[}\])]|=>|->|::|==|!=|&&|\|\||\w\(.*\)\s*[;:{]?\s*$",
+    )
+    .unwrap()
+});
+
+/// Sort copied text into one of the [`ClipKind`]s, by line-level heuristics.
+pub fn classify(text: &str) -> ClipKind {
+    let text = text.trim();
+    if URL.is_match(text) {
+        return ClipKind::Url;
+    }
+    if TRACE_HEADER.is_match(text) || TRACE_FRAME.find_iter(text).count() >= 2 {
+        return ClipKind::StackTrace;
+    }
+    if COMPILER_ERROR.is_match(text) {
+        return ClipKind::CompilerError;
+    }
+    if looks_like_code(text) {
+        return ClipKind::Code;
+    }
+    ClipKind::Prose {
+        language: guess_language(text).map(str::to_string),
+    }
+}
+
+/// Most lines carry a code signal (keyword, trailing `;`/`{`/`}`, operator
+/// or call) and there are at least two of them.
+fn looks_like_code(text: &str) -> bool {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let signals: usize = lines
+        .iter()
+        .map(|line| CODE_LINE.find_iter(line).count())
+        .sum();
+    let code_lines = lines.iter().filter(|line| CODE_LINE.is_match(line)).count();
+    signals >= 2 && code_lines * 3 >= lines.len() * 2
+}
+
+const KANA: (char, char) = ('\u{3040}', '\u{30FF}');
+
+/// Non-Latin scripts, tried in order. Japanese mixes kana with Han
+/// characters, so it is tried first but only when there is any kana.
+const SCRIPTS: &[(&str, &[(char, char)])] = &[
+    ("Japanese", &[KANA, ('\u{4E00}', '\u{9FFF}')]),
+    (
+        "Korean",
+        &[('\u{AC00}', '\u{D7AF}'), ('\u{1100}', '\u{11FF}')],
+    ),
+    (
+        "Chinese",
+        &[('\u{4E00}', '\u{9FFF}'), ('\u{3400}', '\u{4DBF}')],
+    ),
+    ("Russian", &[('\u{0400}', '\u{04FF}')]),
+    ("Greek", &[('\u{0370}', '\u{03FF}')]),
+    ("Arabic", &[('\u{0600}', '\u{06FF}')]),
+    ("Hebrew", &[('\u{0590}', '\u{05FF}')]),
+    ("Hindi", &[('\u{0900}', '\u{097F}')]),
+    ("Thai", &[('\u{0E00}', '\u{0E7F}')]),
+];
+
+/// Common short words of Latin-script languages.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "English",
+        &[
+            "the", "and", "is", "are", "of", "to", "in", "that", "it", "with", "for", "this",
+            "was", "you", "have", "not", "be", "on",
+        ],
+    ),
+    (
+        "Spanish",
+        &[
+            "el", "la", "los", "las", "de", "que", "y", "en", "es", "por", "para", "con", "una",
+            "del", "se", "no", "como", "pero",
+        ],
+    ),
+    (
+        "French",
+        &[
+            "le", "la", "les", "des", "est", "et", "un", "une", "du", "que", "pour", "dans", "pas",
+            "ce", "sur", "avec", "qui", "il",
+        ],
+    ),
+    (
+        "German",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "zu", "mit", "ich", "sie",
+            "es", "auf", "den", "für", "von", "auch",
+        ],
+    ),
+    (
+        "Italian",
+        &[
+            "il", "lo", "gli", "che", "di", "è", "non", "per", "una", "con", "sono", "della",
+            "del", "le", "ma", "anche", "questo",
+        ],
+    ),
+    (
+        "Portuguese",
+        &[
+            "o", "os", "as", "que", "de", "não", "é", "um", "uma", "para", "com", "em", "do", "da",
+            "mas", "por",
+        ],
+    ),
+    (
+        "Dutch",
+        &[
+            "de", "het", "een", "en", "van", "is", "niet", "dat", "ik", "je", "op", "te", "zijn",
+            "met", "voor",
+        ],
+    ),
+];
+
+/// Guess the language of prose: by script when at least a third of the
+/// letters are non-Latin, otherwise by which stopword list matches most.
+pub fn guess_language(text: &str) -> Option<&'static str> {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let in_range = |c: &char, (lo, hi): (char, char)| (lo..=hi).contains(c);
+    let has_kana = letters.iter().any(|c| in_range(c, KANA));
+    for (language, ranges) in SCRIPTS {
+        if *language == "Japanese" && !has_kana {
+            continue;
+        }
+        let count = letters
+            .iter()
+            .filter(|c| ranges.iter().any(|range| in_range(c, *range)))
+            .count();
+        if count * 3 >= letters.len() {
+            return Some(language);
+        }
+    }
+
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let (language, hits) = STOPWORDS
+        .iter()
+        .map(|(language, stopwords)| {
+            let hits = words.iter().filter(|w| stopwords.contains(w)).count();
+            (*language, hits)
+        })
+        // First list wins ties.
+        .rev()
+        .max_by_key(|(_, hits)| *hits)?;
+    // A couple of stray matches in a short snippet prove nothing.
+    (hits >= 2 && hits * 10 >= words.len()).then_some(language)
+}
+
+/// Actions worth offering for a clip of `kind`. Prose in a language other
+/// than `translate_to` can be translated; long prose can be summarized.
+pub fn actions_for(kind: &ClipKind, text: &str, translate_to: &str) -> Vec<ClipAction> {
+    match kind {
+        ClipKind::StackTrace | ClipKind::CompilerError | ClipKind::Code => {
+            vec![ClipAction::Explain]
+        }
+        ClipKind::Url => Vec::new(),
+        ClipKind::Prose { language } => {
+            let mut actions = Vec::new();
+            if text.trim().chars().count() >= SUMMARY_MIN_CHARS {
+                actions.push(ClipAction::Summarize);
+            }
+            if language
+                .as_deref()
+                .is_some_and(|l| !l.eq_ignore_ascii_case(translate_to.trim()))
+            {
+                actions.push(ClipAction::Translate);
+            }
+            actions
+        }
+    }
+}
+
+/// The instruction for running `action` on `text`, with the text fenced as
+/// untrusted clipboard content.
+pub fn action_prompt(
+    action: ClipAction,
+    kind: &ClipKind,
+    text: &str,
+    translate_to: &str,
+) -> String {
+    let instruction = match (action, kind) {
+        (ClipAction::Explain, ClipKind::StackTrace) => {
+            "Explain this stack trace in plain words: what went wrong, where, and the most \
+             likely fix."
+                .to_string()
+        }
+        (ClipAction::Explain, ClipKind::CompilerError) => {
+            "Explain this compiler error in plain words and how to fix it.".to_string()
+        }
+        (ClipAction::Explain, _) => "Explain briefly what this code does.".to_string(),
+        (ClipAction::Summarize, _) => "Summarize this in one or two sentences.".to_string(),
+        (
+            ClipAction::Translate,
+            ClipKind::Prose {
+                language: Some(from),
+            },
+        ) => format!(
+            "Translate this {} text into {}. Reply with the translation only.",
+            from, translate_to
+        ),
+        (ClipAction::Translate, _) => format!(
+            "Translate this text into {}. Reply with the translation only.",
+            translate_to
+        ),
+    };
+    format!("{}\n\n{}", instruction, fence("clipboard", text))
+}
+
+/// Run `action` on `text` through the model. Runs without a mood, whose
+/// sampling could cut the result short; the reply is capped at the
+/// provider's `max_response_length`.
+pub async fn run_action(
+    provider: &dyn ChatProvider,
+    action: ClipAction,
+    text: &str,
+    translate_to: &str,
+) -> AiResult<String> {
+    let kind = classify(text);
+    let prompt = ChatMessage {
+        role: "user".to_string(),
+        content: action_prompt(action, &kind, text, translate_to),
+    };
+    let reply = provider.chat(vec![prompt], None).await?;
+    Ok(trim_reply(&reply, provider.config().max_response_length))
+}
@@ -0,0 +1,172 @@
+use crate::ai::{factory::chat_provider, limits::trim_reply, AiResult, ChatMessage, ChatProvider};
+use crate::config::{AiConfig, AppConfig};
+use crate::monitors::state::{is_within_sleep_time, SpriteState};
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+
+#[cfg(test)]
+#[path = "commentary_test.rs"]
+mod commentary_test;
+
+/// Source of local wall-clock time, replaceable for tests.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> NaiveDateTime;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+/// A state change worth a remark.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Trigger {
+    Entered { state: SpriteState },
+    FinishedWork { minutes: i64 },
+    WokeUp,
+}
+
+impl Trigger {
+    fn describe(&self) -> String {
+        match self {
+            Trigger::Entered {
+                state: SpriteState::Overheating,
+            } => "The computer just started overheating".to_string(),
+            Trigger::Entered {
+                state: SpriteState::Gaming,
+            } => "The user just started playing a game".to_string(),
+            Trigger::Entered {
+                state: SpriteState::LowBattery,
+            } => "The battery just ran low".to_string(),
+            Trigger::Entered { state } => format!("You just switched to the {:?} state", state),
+            Trigger::FinishedWork { minutes } => format!(
+                "The user just stopped working after a {} minute stretch",
+                minutes
+            ),
+            Trigger::WokeUp => "You just woke up".to_string(),
+        }
+    }
+}
+
+/// Payload of the `sprite-says` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpriteSays {
+    pub text: String,
+    pub trigger: Trigger,
+}
+
+/// Watches the monitor's state readings and decides when the sprite should
+/// speak up unprompted.
+pub struct CommentaryEngine {
+    clock: Arc<dyn Clock>,
+    state: Option<SpriteState>,
+    state_since: NaiveDateTime,
+    last_remark: Option<NaiveDateTime>,
+}
+
+impl CommentaryEngine {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now();
+        Self {
+            clock,
+            state: None,
+            state_since: now,
+            last_remark: None,
+        }
+    }
+
+    /// Record the current state. Returns the trigger to remark on when this
+    /// reading completes an interesting transition and a remark is allowed
+    /// now; the remark then counts against the rate limit.
+    pub fn observe(&mut self, state: &SpriteState, config: &AppConfig) -> Option<Trigger> {
+        let now = self.clock.now();
+        let previous = self.state.replace(state.clone())?;
+        if previous == *state {
+            return None;
+        }
+        let stretch = now - self.state_since;
+        self.state_since = now;
+
+        let trigger = transition_trigger(&previous, state, stretch, config)?;
+        if !self.allowed(now, config) {
+            tracing::debug!("Holding back remark on {:?}", trigger);
+            return None;
+        }
+        self.last_remark = Some(now);
+        Some(trigger)
+    }
+
+    fn allowed(&self, now: NaiveDateTime, config: &AppConfig) -> bool {
+        if !config.commentary.enabled {
+            return false;
+        }
+        if config.sleep.enabled
+            && is_within_sleep_time(&config.sleep.start_time, &config.sleep.end_time, now.time())
+        {
+            return false;
+        }
+        let min_interval = TimeDelta::minutes(config.commentary.min_interval_minutes as i64);
+        self.last_remark
+            .is_none_or(|last| now - last >= min_interval)
+    }
+}
+
+fn transition_trigger(
+    from: &SpriteState,
+    to: &SpriteState,
+    stretch: TimeDelta,
+    config: &AppConfig,
+) -> Option<Trigger> {
+    let long_work = TimeDelta::minutes(config.commentary.long_work_minutes as i64);
+    match (from, to) {
+        (_, SpriteState::Overheating) => Some(Trigger::Entered { state: to.clone() }),
+        (SpriteState::Working, _) if stretch >= long_work => Some(Trigger::FinishedWork {
+            minutes: stretch.num_minutes(),
+        }),
+        (SpriteState::Sleeping, _) => Some(Trigger::WokeUp),
+        (_, SpriteState::Gaming | SpriteState::LowBattery) => {
+            Some(Trigger::Entered { state: to.clone() })
+        }
+        _ => None,
+    }
+}
+
+/// Ask the model for a short unprompted remark on `trigger`.
+pub async fn generate_remark(
+    provider: &dyn ChatProvider,
+    trigger: &Trigger,
+    mood: Option<&str>,
+) -> AiResult<String> {
+    let prompt = ChatMessage {
+        role: "user".to_string(),
+        content: format!(
+            "[Event] {}. Nobody asked you anything: make one short remark about it \
+             in character, under 20 words. Do not greet or offer help.",
+            trigger.describe()
+        ),
+    };
+    let reply = provider.chat(vec![prompt], mood).await?;
+    Ok(trim_reply(&reply, provider.config().max_response_length))
+}
+
+/// Generate a remark in the background and emit it as `sprite-says`.
+pub fn spawn_remark(app: AppHandle, ai: AiConfig, trigger: Trigger, mood: String) {
+    tauri::async_runtime::spawn(async move {
+        let provider = chat_provider(&app, ai);
+        match generate_remark(provider.as_ref(), &trigger, Some(&mood)).await {
+            Ok(text) if !text.is_empty() => {
+                if let Err(e) = app.emit("sprite-says", SpriteSays { text, trigger }) {
+                    tracing::error!("Failed to emit sprite-says: {}", e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Remark on {:?} failed: {}", trigger, e),
+        }
+    });
+}
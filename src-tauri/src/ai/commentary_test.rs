@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::commentary::{generate_remark, Clock, CommentaryEngine, Trigger};
+    use crate::ai::stub::{serve, StubResponse};
+    use crate::ai::OllamaClient;
+    use crate::config::{AiConfig, AppConfig};
+    use crate::monitors::state::SpriteState;
+    use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+    use std::sync::{Arc, Mutex};
+
+    /// A clock that only moves when told to.
+    struct FakeClock(Mutex<NaiveDateTime>);
+
+    impl FakeClock {
+        fn new(start: NaiveDateTime) -> Self {
+            Self(Mutex::new(start))
+        }
+
+        fn advance(&self, by: TimeDelta) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> NaiveDateTime {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn clock_at(hour: u32, minute: u32) -> Arc<FakeClock> {
+        let start = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap();
+        Arc::new(FakeClock::new(start))
+    }
+
+    #[test]
+    fn test_first_reading_is_silent() {
+        let clock = clock_at(12, 0);
+        let mut engine = CommentaryEngine::new(clock.clone());
+        let config = AppConfig::default();
+
+        assert_eq!(engine.observe(&SpriteState::Gaming, &config), None);
+        assert_eq!(engine.observe(&SpriteState::Gaming, &config), None);
+    }
+
+    #[test]
+    fn test_remarks_are_rate_limited() {
+        let clock = clock_at(12, 0);
+        let mut engine = CommentaryEngine::new(clock.clone());
+        let config = AppConfig::default();
+
+        engine.observe(&SpriteState::Idle, &config);
+        assert_eq!(
+            engine.observe(&SpriteState::Overheating, &config),
+            Some(Trigger::Entered {
+                state: SpriteState::Overheating
+            })
+        );
+
+        clock.advance(TimeDelta::minutes(5));
+        engine.observe(&SpriteState::Idle, &config);
+        assert_eq!(engine.observe(&SpriteState::Gaming, &config), None);
+
+        clock.advance(TimeDelta::minutes(20));
+        engine.observe(&SpriteState::Idle, &config);
+        assert!(engine.observe(&SpriteState::Gaming, &config).is_some());
+    }
+
+    #[test]
+    fn test_long_work_stretch_ending() {
+        let clock = clock_at(9, 0);
+        let mut engine = CommentaryEngine::new(clock.clone());
+        let config = AppConfig::default();
+
+        engine.observe(&SpriteState::Working, &config);
+        clock.advance(TimeDelta::minutes(10));
+        // Too short to mention.
+        assert_eq!(engine.observe(&SpriteState::Browsing, &config), None);
+
+        engine.observe(&SpriteState::Working, &config);
+        clock.advance(TimeDelta::minutes(90));
+        assert_eq!(
+            engine.observe(&SpriteState::Idle, &config),
+            Some(Trigger::FinishedWork { minutes: 90 })
+        );
+    }
+
+    #[test]
+    fn test_quiet_hours_follow_sleep_config() {
+        let clock = clock_at(23, 30);
+        let mut engine = CommentaryEngine::new(clock.clone());
+        let mut config = AppConfig::default();
+        config.sleep.enabled = true;
+        config.sleep.start_time = "23:00".to_string();
+        config.sleep.end_time = "07:00".to_string();
+
+        engine.observe(&SpriteState::Idle, &config);
+        assert_eq!(engine.observe(&SpriteState::Gaming, &config), None);
+
+        // Past the quiet hours.
+        clock.advance(TimeDelta::hours(8));
+        engine.observe(&SpriteState::Idle, &config);
+        assert!(engine.observe(&SpriteState::Gaming, &config).is_some());
+    }
+
+    #[test]
+    fn test_disabled_commentary() {
+        let clock = clock_at(12, 0);
+        let mut engine = CommentaryEngine::new(clock);
+        let mut config = AppConfig::default();
+        config.commentary.enabled = false;
+
+        engine.observe(&SpriteState::Idle, &config);
+        assert_eq!(engine.observe(&SpriteState::Overheating, &config), None);
+    }
+
+    #[tokio::test]
+    async fn test_generate_remark_describes_event() {
+        let server = serve(vec![StubResponse::json(
+            r#"{"message":{"role":"assistant","content":"  I'm melting over here.  "},"done":true}"#,
+        )]);
+        let client = OllamaClient::new(AiConfig {
+            api_endpoint: server.url.clone(),
+            max_retries: 0,
+            ..AiConfig::default()
+        });
+
+        let remark = generate_remark(
+            &client,
+            &Trigger::Entered {
+                state: SpriteState::Overheating,
+            },
+            Some("Angry"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(remark, "I'm melting over here.");
+
+        let body = &server.requests.lock().unwrap()[0].1;
+        assert!(body.contains("started overheating"));
+        assert!(body.contains("irritable and short-tempered"));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::ai::{AiResult, ChatMessage, ChatProvider, Sampling};
+
+#[cfg(test)]
+#[path = "context_test.rs"]
+mod context_test;
+
+/// Per-message framing overhead (role markers, separators) in chat templates.
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Room kept for the running summary when deciding how much to fold.
+const SUMMARY_ALLOWANCE_TOKENS: usize = 200;
+
+const SUMMARIZER_PROMPT: &str = "You maintain a running summary of a conversation between a user \
+     and Ethereal, a desktop spirit. Merge the previous summary and the new messages into one \
+     concise summary of under 120 words. Keep facts, names, preferences and open questions. \
+     Reply with the summary only.";
+
+/// Rough token count without a tokenizer: about four ASCII characters per
+/// token, and one token per non-ASCII character (CJK, emoji).
+pub fn estimate_tokens(text: &str) -> usize {
+    let (ascii, other) = text.chars().fold((0usize, 0usize), |(ascii, other), c| {
+        if c.is_ascii() {
+            (ascii + 1, other)
+        } else {
+            (ascii, other + 1)
+        }
+    });
+    ascii.div_ceil(4) + other
+}
+
+pub fn estimate_message_tokens(messages: &[ChatMessage]) -> usize {
+    messages
+        .iter()
+        .map(|m| estimate_tokens(&m.content) + MESSAGE_OVERHEAD_TOKENS)
+        .sum()
+}
+
+/// How many of the oldest `history` messages must be folded into the summary
+/// so that `fixed_tokens` plus the rest fits in `budget`.
+///
+/// The last `keep_recent` messages are only folded when they alone overflow
+/// the budget. A `budget` of `0` disables compaction.
+pub fn messages_to_fold(
+    history: &[ChatMessage],
+    fixed_tokens: usize,
+    budget: usize,
+    keep_recent: usize,
+) -> usize {
+    if budget == 0 || fixed_tokens + estimate_message_tokens(history) <= budget {
+        return 0;
+    }
+
+    let mut fold = history.len().saturating_sub(keep_recent);
+    while fold < history.len()
+        && fixed_tokens + SUMMARY_ALLOWANCE_TOKENS + estimate_message_tokens(&history[fold..])
+            > budget
+    {
+        fold += 1;
+    }
+    fold
+}
+
+pub fn summary_message(summary: &str) -> ChatMessage {
+    ChatMessage {
+        role: "system".to_string(),
+        content: format!("Summary of the earlier conversation: {}", summary),
+    }
+}
+
+/// Ask the model to merge `messages` into the `previous` running summary.
+pub async fn summarize(
+    provider: &dyn ChatProvider,
+    previous: Option<&str>,
+    messages: &[ChatMessage],
+) -> AiResult<String> {
+    let transcript = messages
+        .iter()
+        .map(|m| {
+            let speaker = if m.role == "assistant" {
+                "Ethereal"
+            } else {
+                "User"
+            };
+            format!("{}: {}", speaker, m.content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let request = vec![
+        ChatMessage {
+            role: "system".to_string(),
+            content: SUMMARIZER_PROMPT.to_string(),
+        },
+        ChatMessage {
+            role: "user".to_string(),
+            content: format!(
+                "Previous summary: {}\n\nNew messages:\n{}",
+                previous.unwrap_or("(none)"),
+                transcript
+            ),
+        },
+    ];
+
+    let sampling = Sampling {
+        temperature: Some(0.2),
+        max_tokens: Some(SUMMARY_ALLOWANCE_TOKENS as u32),
+        ..Sampling::default()
+    };
+    let summary = provider.complete(request, sampling).await?;
+    Ok(summary.trim().to_string())
+}
+
+pub struct CompactedHistory {
+    /// History to send: the summary (if any) followed by the kept turns.
+    pub messages: Vec<ChatMessage>,
+    pub summary: Option<String>,
+    /// Number of leading messages newly folded into `summary`.
+    pub folded: usize,
+}
+
+/// Fit `history` into `AiConfig.context_budget_tokens`, folding older turns
+/// into a model-written running summary. If summarizing fails the old turns
+/// are left out of this request only and `folded` is 0, so the stored
+/// conversation keeps them for the next attempt.
+pub async fn compact(
+    provider: &dyn ChatProvider,
+    summary: Option<String>,
+    mut history: Vec<ChatMessage>,
+    fixed_tokens: usize,
+) -> CompactedHistory {
+    let config = provider.config();
+    let summary_tokens = summary
+        .as_deref()
+        .map(|s| estimate_message_tokens(&[summary_message(s)]))
+        .unwrap_or(0);
+
+    let fold = messages_to_fold(
+        &history,
+        fixed_tokens + summary_tokens,
+        config.context_budget_tokens,
+        config.keep_recent_messages,
+    );
+
+    let mut summary = summary;
+    let mut folded = 0;
+    if fold > 0 {
+        let old: Vec<ChatMessage> = history.drain(..fold).collect();
+        match summarize(provider, summary.as_deref(), &old).await {
+            Ok(s) if !s.is_empty() => {
+                summary = Some(s);
+                folded = fold;
+            }
+            Ok(_) => tracing::warn!("Empty chat summary, leaving old turns out of this request"),
+            Err(e) => tracing::warn!(
+                "Failed to summarize chat history, leaving old turns out of this request: {}",
+                e
+            ),
+        }
+    }
+
+    let mut messages = Vec::with_capacity(history.len() + 1);
+    if let Some(s) = summary.as_deref() {
+        messages.push(summary_message(s));
+    }
+    messages.extend(history);
+
+    CompactedHistory {
+        messages,
+        summary,
+        folded,
+    }
+}
@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::context::{compact, estimate_message_tokens, estimate_tokens, messages_to_fold};
+    use crate::ai::{AiError, AiResult, ChatMessage, ChatProvider, Sampling, TokenSink};
+    use crate::config::AiConfig;
+    use futures::future::BoxFuture;
+    use std::sync::Mutex;
+
+    /// Provider that answers every request with a fixed summary.
+    struct FakeProvider {
+        config: AiConfig,
+        reply: Result<String, String>,
+        requests: Mutex<Vec<Vec<ChatMessage>>>,
+    }
+
+    impl FakeProvider {
+        fn new(budget: usize, keep_recent: usize, reply: Result<String, String>) -> Self {
+            Self {
+                config: AiConfig {
+                    context_budget_tokens: budget,
+                    keep_recent_messages: keep_recent,
+                    ..AiConfig::default()
+                },
+                reply,
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl ChatProvider for FakeProvider {
+        fn config(&self) -> &AiConfig {
+            &self.config
+        }
+
+        fn complete<'a>(
+            &'a self,
+            messages: Vec<ChatMessage>,
+            _sampling: Sampling,
+        ) -> BoxFuture<'a, AiResult<String>> {
+            self.requests.lock().unwrap().push(messages);
+            let reply = match &self.reply {
+                Ok(s) => Ok(s.clone()),
+                Err(e) => Err(AiError::other(e.clone())),
+            };
+            Box::pin(async move { reply })
+        }
+
+        fn complete_stream<'a>(
+            &'a self,
+            messages: Vec<ChatMessage>,
+            sampling: Sampling,
+            _on_token: TokenSink<'a>,
+        ) -> BoxFuture<'a, AiResult<String>> {
+            self.complete(messages, sampling)
+        }
+    }
+
+    fn turns(n: usize, words: usize) -> Vec<ChatMessage> {
+        (0..n)
+            .map(|i| ChatMessage {
+                role: if i % 2 == 0 { "user" } else { "assistant" }.to_string(),
+                content: format!("m{} {}", i, "word ".repeat(words)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("你好"), 2);
+        assert_eq!(
+            estimate_message_tokens(&turns(2, 0)),
+            2 * (estimate_tokens("m0 ") + 4)
+        );
+    }
+
+    #[test]
+    fn test_no_fold_within_budget_or_unlimited() {
+        let history = turns(10, 10);
+        assert_eq!(messages_to_fold(&history, 100, 10_000, 4), 0);
+        assert_eq!(messages_to_fold(&history, 100_000, 0, 4), 0);
+    }
+
+    #[test]
+    fn test_fold_keeps_recent_turns() {
+        let history = turns(20, 40);
+        let per_message = estimate_message_tokens(&history[..1]);
+        let budget = 100 + 200 + per_message * 6;
+
+        assert_eq!(messages_to_fold(&history, 100, budget, 4), 16);
+    }
+
+    #[test]
+    fn test_fold_eats_into_recent_when_they_overflow() {
+        let history = turns(10, 40);
+        let per_message = estimate_message_tokens(&history[..1]);
+        let budget = 100 + 200 + per_message * 2;
+
+        assert_eq!(messages_to_fold(&history, 100, budget, 6), 8);
+    }
+
+    #[tokio::test]
+    async fn test_compact_folds_into_model_summary() {
+        let provider = FakeProvider::new(400, 2, Ok("They talked about fans.".to_string()));
+        let history = turns(12, 30);
+
+        let compacted = compact(&provider, Some("Earlier: hello.".to_string()), history, 50).await;
+
+        assert!(compacted.folded >= 10);
+        assert_eq!(
+            compacted.summary.as_deref(),
+            Some("They talked about fans.")
+        );
+        assert_eq!(compacted.messages[0].role, "system");
+        assert!(compacted.messages[0]
+            .content
+            .contains("They talked about fans."));
+        assert_eq!(compacted.messages.len(), 12 - compacted.folded + 1);
+
+        let requests = provider.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0][1].content.contains("Earlier: hello."));
+        assert!(requests[0][1].content.contains("User: m0"));
+    }
+
+    #[tokio::test]
+    async fn test_compact_without_overflow_leaves_history() {
+        let provider = FakeProvider::new(10_000, 2, Ok("unused".to_string()));
+        let compacted = compact(&provider, None, turns(4, 5), 50).await;
+
+        assert_eq!(compacted.folded, 0);
+        assert_eq!(compacted.messages.len(), 4);
+        assert!(provider.requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_compact_truncates_without_folding_when_summary_fails() {
+        let provider = FakeProvider::new(400, 2, Err("offline".to_string()));
+        let compacted = compact(&provider, Some("Old.".to_string()), turns(12, 30), 50).await;
+
+        // Nothing is folded, so the store keeps the turns for a retry...
+        assert_eq!(compacted.folded, 0);
+        assert_eq!(compacted.summary.as_deref(), Some("Old."));
+        // ...but this request still only gets the recent window.
+        assert!(compacted.messages.len() < 12 + 1);
+        assert!(compacted.messages[0].content.contains("Old."));
+        assert!(compacted
+            .messages
+            .last()
+            .unwrap()
+            .content
+            .starts_with("m11"));
+    }
+}
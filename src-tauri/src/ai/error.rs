@@ -0,0 +1,141 @@
+use serde::Serialize;
+
+#[cfg(test)]
+#[path = "error_test.rs"]
+mod error_test;
+
+/// Longest error body kept from an HTTP response.
+const MAX_BODY_CHARS: usize = 500;
+
+pub type AiResult<T> = Result<T, AiError>;
+
+/// Failure talking to the LLM backend, serialized to the frontend as
+/// `{ "kind": "...", ... }` so the sprite can react to each case.
+#[derive(Debug, Clone, Serialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AiError {
+    #[error("Cannot reach the AI server at {endpoint}")]
+    ConnectionRefused { endpoint: String },
+    #[error("The AI server took too long to answer")]
+    Timeout,
+    #[error("AI server returned {status}: {body}")]
+    Http { status: u16, body: String },
+    #[error("Model {model} is not installed")]
+    ModelMissing { model: String },
+    #[error("Unexpected response from the AI server: {message}")]
+    Parse { message: String },
+    #[error("Chat is cooling down, try again in {remaining_secs}s")]
+    Cooldown { remaining_secs: u64 },
+    #[error("Request was cancelled")]
+    Cancelled,
+    #[error("{message}")]
+    Other { message: String },
+}
+
+impl AiError {
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::Parse {
+            message: message.into(),
+        }
+    }
+
+    pub fn other(message: impl Into<String>) -> Self {
+        Self::Other {
+            message: message.into(),
+        }
+    }
+
+    /// Failures worth another attempt: the server is still starting or
+    /// warming up the model, or is briefly overloaded. Timeouts are not
+    /// retried since each one already cost the full request timeout.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::ConnectionRefused { .. } => true,
+            Self::Http { status, .. } => matches!(status, 429 | 500 | 502 | 503 | 504),
+            _ => false,
+        }
+    }
+
+    /// The `kind` tag this error is serialized with.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ConnectionRefused { .. } => "connection_refused",
+            Self::Timeout => "timeout",
+            Self::Http { .. } => "http",
+            Self::ModelMissing { .. } => "model_missing",
+            Self::Parse { .. } => "parse",
+            Self::Cooldown { .. } => "cooldown",
+            Self::Cancelled => "cancelled",
+            Self::Other { .. } => "other",
+        }
+    }
+
+    /// No AI server is listening, so the canned fallback should answer.
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, Self::ConnectionRefused { .. })
+    }
+
+    /// Classify a non-success response. Both Ollama and OpenAI-compatible
+    /// servers answer 404 with a "model not found" body for unknown models.
+    pub fn from_status(status: u16, body: &str, model: &str) -> Self {
+        let lower = body.to_lowercase();
+        if status == 404
+            && lower.contains("model")
+            && (lower.contains("not found") || lower.contains("does not exist"))
+        {
+            return Self::ModelMissing {
+                model: model.to_string(),
+            };
+        }
+
+        let mut body: String = body.trim().chars().take(MAX_BODY_CHARS).collect();
+        if body.is_empty() {
+            body = "(empty body)".to_string();
+        }
+        Self::Http { status, body }
+    }
+}
+
+/// Pass successful responses through; turn others into an [`AiError`]
+/// carrying the response body.
+pub async fn check_response(res: reqwest::Response, model: &str) -> AiResult<reqwest::Response> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+    let body = res.text().await.unwrap_or_default();
+    Err(AiError::from_status(status.as_u16(), &body, model))
+}
+
+impl From<reqwest::Error> for AiError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Self::Timeout
+        } else if e.is_connect() {
+            Self::ConnectionRefused {
+                endpoint: e
+                    .url()
+                    .map(|u| u.origin().ascii_serialization())
+                    .unwrap_or_default(),
+            }
+        } else if e.is_decode() {
+            Self::parse(e.to_string())
+        } else if let Some(status) = e.status() {
+            Self::from_status(status.as_u16(), "", "")
+        } else {
+            Self::other(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for AiError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::parse(e.to_string())
+    }
+}
+
+impl From<anyhow::Error> for AiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::other(e.to_string())
+    }
+}
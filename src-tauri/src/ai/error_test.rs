@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::AiError;
+
+    #[test]
+    fn test_not_found_body_means_missing_model() {
+        let ollama = AiError::from_status(
+            404,
+            r#"{"error":"model \"llama3.3\" not found, try pulling it first"}"#,
+            "llama3.3",
+        );
+        assert!(matches!(ollama, AiError::ModelMissing { model } if model == "llama3.3"));
+
+        let openai = AiError::from_status(
+            404,
+            r#"{"error":{"message":"The model `gpt-5` does not exist","code":"model_not_found"}}"#,
+            "gpt-5",
+        );
+        assert!(matches!(openai, AiError::ModelMissing { .. }));
+
+        let other = AiError::from_status(404, "404 page not found", "llama3.2");
+        assert!(matches!(other, AiError::Http { status: 404, .. }));
+    }
+
+    #[test]
+    fn test_http_body_is_truncated() {
+        let body = "x".repeat(2000);
+        match AiError::from_status(500, &body, "m") {
+            AiError::Http { status, body } => {
+                assert_eq!(status, 500);
+                assert_eq!(body.chars().count(), 500);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_serialized_as_tagged_object() {
+        let json = serde_json::to_value(AiError::Cooldown { remaining_secs: 3 }).unwrap();
+        assert_eq!(json["kind"], "cooldown");
+        assert_eq!(json["remaining_secs"], 3);
+
+        let json = serde_json::to_value(AiError::Timeout).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "timeout" }));
+
+        let json = serde_json::to_value(AiError::ConnectionRefused {
+            endpoint: "http://localhost:11434".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json["kind"], "connection_refused");
+    }
+
+    #[test]
+    fn test_kind_matches_serialized_tag() {
+        for error in [
+            AiError::Timeout,
+            AiError::Cancelled,
+            AiError::parse("bad json"),
+            AiError::other("boom"),
+            AiError::from_status(503, "loading", "m"),
+            AiError::from_status(404, "model not found", "m"),
+        ] {
+            let json = serde_json::to_value(&error).unwrap();
+            assert_eq!(json["kind"], error.kind());
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::ai::{metrics::AiMetrics, ChatProvider, OllamaClient, OpenAiClient};
+use crate::config::AiConfig;
+use tauri::{AppHandle, Manager};
+
+pub fn create_provider(config: AiConfig) -> Box<dyn ChatProvider> {
+    create_provider_with_metrics(config, None)
+}
+
+/// Like [`create_provider`], recording chat requests into `metrics`.
+pub fn create_provider_with_metrics(
+    config: AiConfig,
+    metrics: Option<AiMetrics>,
+) -> Box<dyn ChatProvider> {
+    match config.provider.as_str() {
+        "openai" => {
+            let client = OpenAiClient::new(config);
+            match metrics {
+                Some(metrics) => Box::new(client.with_metrics(metrics)),
+                None => Box::new(client),
+            }
+        }
+        other => {
+            if other != "ollama" {
+                tracing::warn!("Unknown AI provider '{}', falling back to ollama", other);
+            }
+            let client = OllamaClient::new(config);
+            match metrics {
+                Some(metrics) => Box::new(client.with_metrics(metrics)),
+                None => Box::new(client),
+            }
+        }
+    }
+}
+
+/// Provider for chat on behalf of the app, recording into its [`AiMetrics`].
+pub fn chat_provider(app: &AppHandle, config: AiConfig) -> Box<dyn ChatProvider> {
+    let metrics = app.try_state::<AiMetrics>().map(|m| m.inner().clone());
+    create_provider_with_metrics(config, metrics)
+}
@@ -0,0 +1,222 @@
+use crate::monitors::GpuStats;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[cfg(test)]
+#[path = "fallback_test.rs"]
+mod fallback_test;
+
+/// Phrase banks shipped with the app.
+const BUNDLED: &str = include_str!("phrases.toml");
+
+/// Name of the user's phrase file in the config directory.
+pub const USER_FILE: &str = "phrases.toml";
+
+/// Canned lines for chatting without a model, keyed by intent, mood and
+/// state. See `phrases.toml` for the file format.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PhraseBank {
+    #[serde(default)]
+    pub general: Vec<String>,
+    #[serde(default)]
+    pub intents: BTreeMap<String, Intent>,
+    #[serde(default)]
+    pub moods: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub states: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Intent {
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub priority: i32,
+    pub replies: Vec<String>,
+}
+
+/// Values for the placeholders in canned lines; `None` when unknown.
+#[derive(Debug, Clone, Default)]
+pub struct FallbackVars {
+    pub time: Option<String>,
+    pub cpu: Option<String>,
+    pub temperature: Option<String>,
+    pub memory: Option<String>,
+    pub battery: Option<String>,
+    pub battery_state: Option<String>,
+    pub mood: Option<String>,
+    pub state: Option<String>,
+}
+
+impl FallbackVars {
+    pub fn new(stats: Option<&GpuStats>, mood: Option<&str>, time: String) -> Self {
+        let mut vars = Self {
+            time: Some(time),
+            mood: mood.map(str::to_string),
+            ..Self::default()
+        };
+        if let Some(s) = stats {
+            vars.cpu = Some(format!("{:.0}%", s.utilization));
+            vars.temperature = Some(format!("{:.0}°C", s.temperature));
+            if s.memory_total > 0 {
+                vars.memory = Some(format!(
+                    "{:.0}%",
+                    s.memory_used as f64 * 100.0 / s.memory_total as f64
+                ));
+            }
+            // Monitors report "N/A" on machines without a battery.
+            if s.battery_state != "N/A" {
+                vars.battery = Some(format!("{:.0}%", s.battery_level));
+                vars.battery_state = Some(s.battery_state.to_lowercase());
+            }
+            vars.state = Some(s.state.clone());
+            vars.mood = vars.mood.or_else(|| Some(s.mood.clone()));
+        }
+        vars
+    }
+
+    fn placeholders(&self) -> [(&'static str, Option<String>); 8] {
+        let words = |name: &Option<String>| name.as_deref().map(|n| key(n).replace('_', " "));
+        [
+            ("{time}", self.time.clone()),
+            ("{cpu}", self.cpu.clone()),
+            ("{temperature}", self.temperature.clone()),
+            ("{memory}", self.memory.clone()),
+            ("{battery}", self.battery.clone()),
+            ("{battery_state}", self.battery_state.clone()),
+            ("{mood}", words(&self.mood)),
+            ("{state}", words(&self.state)),
+        ]
+    }
+
+    /// Fill in `line`, or `None` if it needs a value we don't have.
+    fn fill(&self, line: &str) -> Option<String> {
+        let mut line = line.to_string();
+        for (placeholder, value) in self.placeholders() {
+            if line.contains(placeholder) {
+                let value = value.filter(|v| !v.is_empty())?;
+                line = line.replace(placeholder, &value);
+            }
+        }
+        Some(line)
+    }
+}
+
+impl PhraseBank {
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(source)?)
+    }
+
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED).expect("bundled phrases.toml is valid")
+    }
+
+    /// The bundled banks with the user's `phrases.toml` in `config_dir`
+    /// applied on top. A broken user file is logged and ignored.
+    pub fn load(config_dir: &Path) -> Self {
+        let mut bank = Self::bundled();
+        let path = config_dir.join(USER_FILE);
+        if !path.exists() {
+            return bank;
+        }
+        match std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|source| Self::parse(&source))
+        {
+            Ok(user) => bank.merge(user),
+            Err(e) => tracing::warn!("Ignoring invalid {}: {}", path.display(), e),
+        }
+        bank
+    }
+
+    /// Replace every list and intent that `other` defines.
+    pub fn merge(&mut self, other: PhraseBank) {
+        if !other.general.is_empty() {
+            self.general = other.general;
+        }
+        self.intents.extend(other.intents);
+        self.moods.extend(other.moods);
+        self.states.extend(other.states);
+    }
+
+    /// The intent whose keywords appear in `message`, preferring higher
+    /// priorities. Keywords match whole words, case-insensitively.
+    pub fn intent(&self, message: &str) -> Option<&str> {
+        let words: Vec<String> = message
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_string)
+            .collect();
+        let text = format!(" {} ", words.join(" "));
+
+        self.intents
+            .iter()
+            .filter(|(_, intent)| {
+                intent
+                    .keywords
+                    .iter()
+                    .any(|k| text.contains(&format!(" {} ", k.trim().to_lowercase())))
+            })
+            // max_by_key keeps the last maximum; reverse so ties go to the
+            // first intent in name order.
+            .rev()
+            .max_by_key(|(_, intent)| intent.priority)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Pick a reply to `message`: a matching intent's line if one can be
+    /// filled in, otherwise one for the current state or mood, otherwise a
+    /// general line. `message` is `None` for text the user didn't type.
+    pub fn respond<R: Rng>(
+        &self,
+        message: Option<&str>,
+        vars: &FallbackVars,
+        rng: &mut R,
+    ) -> String {
+        let fill_all = |lines: &[String]| -> Vec<String> {
+            lines.iter().filter_map(|line| vars.fill(line)).collect()
+        };
+
+        if let Some(intent) = message.and_then(|m| self.intent(m)) {
+            let lines = fill_all(&self.intents[intent].replies);
+            if let Some(line) = lines.choose(rng) {
+                return line.clone();
+            }
+        }
+
+        let mut lines = Vec::new();
+        if let Some(lines_for_state) = vars.state.as_deref().and_then(|s| self.states.get(&key(s)))
+        {
+            lines.extend(fill_all(lines_for_state));
+        }
+        if let Some(lines_for_mood) = vars.mood.as_deref().and_then(|m| self.moods.get(&key(m))) {
+            lines.extend(fill_all(lines_for_mood));
+        }
+        if lines.is_empty() {
+            lines = fill_all(&self.general);
+        }
+        lines
+            .choose(rng)
+            .cloned()
+            .unwrap_or_else(|| "...".to_string())
+    }
+}
+
+/// Bank key for a state or mood name: `HighLoad` -> `high_load`.
+fn key(name: &str) -> String {
+    let mut key = String::new();
+    for (i, c) in name.trim().chars().enumerate() {
+        if c.is_whitespace() {
+            key.push('_');
+        } else {
+            if c.is_uppercase() && i > 0 && !key.ends_with('_') {
+                key.push('_');
+            }
+            key.extend(c.to_lowercase());
+        }
+    }
+    key
+}
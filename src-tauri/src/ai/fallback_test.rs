@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::fallback::{FallbackVars, PhraseBank};
+    use crate::monitors::GpuStats;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn bank() -> PhraseBank {
+        PhraseBank::parse(
+            r#"
+            general = ["I'm offline."]
+
+            [intents.time]
+            keywords = ["time", "clock"]
+            priority = 1
+            replies = ["It's {time}."]
+
+            [intents.cpu]
+            keywords = ["cpu", "how busy"]
+            priority = 1
+            replies = ["CPU at {cpu}."]
+
+            [intents.greeting]
+            keywords = ["hi", "hello"]
+            replies = ["Hi! I'm {mood}."]
+
+            [moods]
+            tired = ["*yawns*"]
+
+            [states]
+            high_load = ["Busy at {cpu}!"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn stats() -> GpuStats {
+        GpuStats {
+            temperature: 71.0,
+            utilization: 87.4,
+            cpu_utilization: None,
+            gpu_utilization: None,
+            memory_used: 8_000,
+            memory_total: 16_000,
+            vram: None,
+            network_rx: 0,
+            network_tx: 0,
+            disk_read: 0,
+            disk_write: 0,
+            battery_level: 40.0,
+            battery_state: "Discharging".to_string(),
+            power_draw: None,
+            overheating_sensor: None,
+            active_window: "editor".to_string(),
+            active_category: "Work".to_string(),
+            state: "HighLoad".to_string(),
+            mood: "Tired".to_string(),
+        }
+    }
+
+    fn respond(bank: &PhraseBank, message: Option<&str>, vars: &FallbackVars) -> String {
+        bank.respond(message, vars, &mut StdRng::seed_from_u64(7))
+    }
+
+    #[test]
+    fn test_bundled_banks_parse() {
+        let bank = PhraseBank::bundled();
+        for intent in ["time", "cpu", "battery", "greeting"] {
+            assert!(bank.intents.contains_key(intent), "missing {}", intent);
+        }
+        assert!(!bank.general.is_empty());
+        assert!(bank.states.contains_key("low_battery"));
+        assert!(bank.moods.contains_key("sleeping"));
+    }
+
+    #[test]
+    fn test_keyword_intents() {
+        let bank = bank();
+        assert_eq!(bank.intent("What TIME is it?"), Some("time"));
+        assert_eq!(bank.intent("hello there"), Some("greeting"));
+        assert_eq!(bank.intent("so, how busy are you"), Some("cpu"));
+        // Higher priority wins over the greeting.
+        assert_eq!(bank.intent("hi, what's the time"), Some("time"));
+        // Keywords match whole words only.
+        assert_eq!(bank.intent("this is chill"), None);
+    }
+
+    #[test]
+    fn test_intent_replies_fill_in_readings() {
+        let vars = FallbackVars::new(Some(&stats()), None, "14:05".to_string());
+        let bank = bank();
+        assert_eq!(respond(&bank, Some("time?"), &vars), "It's 14:05.");
+        assert_eq!(respond(&bank, Some("cpu?"), &vars), "CPU at 87%.");
+        assert_eq!(respond(&bank, Some("hi"), &vars), "Hi! I'm tired.");
+    }
+
+    #[test]
+    fn test_state_and_mood_lines_without_intent() {
+        let vars = FallbackVars::new(Some(&stats()), None, "14:05".to_string());
+        let reply = respond(&bank(), Some("tell me a story"), &vars);
+        assert!(
+            ["Busy at 87%!", "*yawns*"].contains(&reply.as_str()),
+            "{}",
+            reply
+        );
+    }
+
+    #[test]
+    fn test_lines_needing_missing_readings_are_skipped() {
+        // Before the first monitor reading there is no CPU figure.
+        let vars = FallbackVars::new(None, None, "14:05".to_string());
+        assert_eq!(respond(&bank(), Some("cpu?"), &vars), "I'm offline.");
+    }
+
+    #[test]
+    fn test_untyped_messages_skip_intents() {
+        let vars = FallbackVars::new(None, Some("Tired"), "14:05".to_string());
+        assert_eq!(respond(&bank(), None, &vars), "*yawns*");
+    }
+
+    #[test]
+    fn test_no_battery_leaves_placeholder_unfilled() {
+        let mut bank = bank();
+        bank.merge(
+            PhraseBank::parse(
+                r#"
+                [intents.battery]
+                keywords = ["battery"]
+                replies = ["{battery} left."]
+                "#,
+            )
+            .unwrap(),
+        );
+        let desktop = GpuStats {
+            battery_state: "N/A".to_string(),
+            power_draw: None,
+            ..stats()
+        };
+        let vars = FallbackVars::new(Some(&desktop), None, "14:05".to_string());
+        assert_ne!(respond(&bank, Some("battery?"), &vars), "0% left.");
+
+        let vars = FallbackVars::new(Some(&stats()), None, "14:05".to_string());
+        assert_eq!(respond(&bank, Some("battery?"), &vars), "40% left.");
+    }
+
+    #[test]
+    fn test_user_file_overrides_bundled_lists() {
+        let dir = std::env::temp_dir().join(format!("ethereal-phrases-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("phrases.toml"),
+            "[moods]\nhappy = [\"Custom happy line\"]\n",
+        )
+        .unwrap();
+
+        let bank = PhraseBank::load(&dir);
+        assert_eq!(bank.moods["happy"], vec!["Custom happy line".to_string()]);
+        // Everything else is still bundled.
+        assert!(bank.moods.contains_key("sad"));
+        assert!(bank.intents.contains_key("time"));
+
+        std::fs::write(dir.join("phrases.toml"), "moods = 3").unwrap();
+        let bank = PhraseBank::load(&dir);
+        assert_ne!(bank.moods["happy"], vec!["Custom happy line".to_string()]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
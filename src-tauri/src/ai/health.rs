@@ -0,0 +1,116 @@
+use crate::ai::{factory::create_provider, AiError, ChatProvider};
+use crate::config::{AiConfig, AppConfig, ConfigState};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[cfg(test)]
+#[path = "health_test.rs"]
+mod health_test;
+
+/// Health probes should fail fast rather than wait out the chat timeout.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often `ai-status` is emitted.
+const STATUS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A model installed on (or served by) the LLM server.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ModelInfo {
+    pub name: String,
+    /// Size on disk in bytes, when the server reports it.
+    pub size: Option<u64>,
+    pub family: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AiStatus {
+    pub provider: String,
+    pub endpoint: String,
+    pub reachable: bool,
+    pub version: Option<String>,
+    /// The model in use: the active persona's, else `AiConfig.model_name`.
+    pub model: String,
+    pub model_installed: bool,
+    /// Why the backend is unusable, if it is.
+    pub error: Option<AiError>,
+}
+
+/// Whether the installed model `installed` satisfies the configured `wanted`
+/// name. Ollama reports untagged models as `name:latest`.
+pub fn model_matches(installed: &str, wanted: &str) -> bool {
+    installed == wanted || (!wanted.contains(':') && installed == format!("{}:latest", wanted))
+}
+
+/// Probe the server behind `provider` and check its configured model.
+pub async fn check_status(provider: &dyn ChatProvider) -> AiStatus {
+    let config = provider.config();
+    let mut status = AiStatus {
+        provider: config.provider.clone(),
+        endpoint: config.api_endpoint.clone(),
+        reachable: false,
+        version: None,
+        model: config.active_model().to_string(),
+        model_installed: false,
+        error: None,
+    };
+
+    let models = match provider.list_models().await {
+        Ok(models) => models,
+        Err(e) => {
+            status.error = Some(e);
+            return status;
+        }
+    };
+    status.reachable = true;
+    status.model_installed = models
+        .iter()
+        .any(|m| model_matches(&m.name, config.active_model()));
+    if !status.model_installed {
+        status.error = Some(AiError::ModelMissing {
+            model: config.active_model().to_string(),
+        });
+    }
+
+    status.version = provider.server_version().await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to query AI server version: {}", e);
+        None
+    });
+    status
+}
+
+fn current_ai_config(app: &AppHandle) -> AiConfig {
+    match app.try_state::<ConfigState>() {
+        Some(state) => state.0.read().unwrap().ai.clone(),
+        None => AppConfig::load(app).unwrap_or_default().ai,
+    }
+}
+
+/// Emit `ai-status` now and then every [`STATUS_INTERVAL`].
+pub fn spawn_status_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let provider = create_provider(current_ai_config(&app));
+            let status = check_status(provider.as_ref()).await;
+            if let Err(e) = app.emit("ai-status", &status) {
+                tracing::error!("Failed to emit ai-status: {}", e);
+            }
+            tokio::time::sleep(STATUS_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn ai_status(app: AppHandle) -> Result<AiStatus, AiError> {
+    let provider = create_provider(current_ai_config(&app));
+    Ok(check_status(provider.as_ref()).await)
+}
+
+/// Models installed on the configured server, for the settings dropdown.
+#[tauri::command]
+pub async fn ai_list_models(app: AppHandle) -> Result<Vec<ModelInfo>, AiError> {
+    let provider = create_provider(current_ai_config(&app));
+    provider.list_models().await
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::health::{check_status, model_matches};
+    use crate::ai::stub::{serve, StubResponse};
+    use crate::ai::{AiError, ChatProvider, OllamaClient, OpenAiClient};
+    use crate::config::AiConfig;
+
+    const TAGS: &str = r#"{"models":[{"name":"llama3.2:latest","model":"llama3.2:latest","modified_at":"2024-10-01T12:00:00Z","size":2019393189,"digest":"a80c4f17acd5","details":{"parent_model":"","format":"gguf","family":"llama","families":["llama"],"parameter_size":"3.2B","quantization_level":"Q4_K_M"}},{"name":"qwen2.5:7b","size":4683087332,"details":{"family":"qwen2"}}]}"#;
+
+    fn ollama_at(url: &str, model: &str) -> OllamaClient {
+        OllamaClient::new(AiConfig {
+            api_endpoint: url.to_string(),
+            model_name: model.to_string(),
+            ..AiConfig::default()
+        })
+    }
+
+    /// An endpoint nothing is listening on.
+    fn closed_endpoint() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        format!("http://127.0.0.1:{}", port)
+    }
+
+    #[test]
+    fn test_model_matches_latest_tag() {
+        assert!(model_matches("llama3.2:latest", "llama3.2"));
+        assert!(model_matches("qwen2.5:7b", "qwen2.5:7b"));
+        assert!(!model_matches("qwen2.5:7b", "qwen2.5"));
+        assert!(!model_matches("llama3.2:1b", "llama3.2:latest"));
+    }
+
+    #[tokio::test]
+    async fn test_list_models_reads_tags() {
+        let server = serve(vec![StubResponse::json(TAGS)]);
+        let client = ollama_at(&server.url, "llama3.2");
+
+        let models = client.list_models().await.unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].name, "llama3.2:latest");
+        assert_eq!(models[0].size, Some(2019393189));
+        assert_eq!(models[0].family.as_deref(), Some("llama"));
+        assert_eq!(models[0].parameter_size.as_deref(), Some("3.2B"));
+        assert_eq!(models[1].quantization, None);
+        assert_eq!(server.requests.lock().unwrap()[0].0, "/api/tags");
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_installed_model_and_version() {
+        let server = serve(vec![
+            StubResponse::json(TAGS),
+            StubResponse::json(r#"{"version":"0.5.1"}"#),
+        ]);
+        let client = ollama_at(&server.url, "llama3.2");
+
+        let status = check_status(&client).await;
+        assert!(status.reachable);
+        assert!(status.model_installed);
+        assert_eq!(status.version.as_deref(), Some("0.5.1"));
+        assert!(status.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_missing_model() {
+        let server = serve(vec![
+            StubResponse::json(TAGS),
+            StubResponse::json(r#"{"version":"0.5.1"}"#),
+        ]);
+        let client = ollama_at(&server.url, "mistral");
+
+        let status = check_status(&client).await;
+        assert!(status.reachable);
+        assert!(!status.model_installed);
+        assert!(matches!(
+            status.error,
+            Some(AiError::ModelMissing { ref model }) if model == "mistral"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_unreachable_server() {
+        let client = ollama_at(&closed_endpoint(), "llama3.2");
+
+        let status = check_status(&client).await;
+        assert!(!status.reachable);
+        assert!(!status.model_installed);
+        assert!(matches!(
+            status.error,
+            Some(AiError::ConnectionRefused { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_openai_lists_served_models() {
+        let server = serve(vec![StubResponse::json(
+            r#"{"object":"list","data":[{"id":"qwen2.5-7b-instruct","object":"model"}]}"#,
+        )]);
+        let client = OpenAiClient::new(AiConfig {
+            provider: "openai".to_string(),
+            api_endpoint: server.url.clone(),
+            model_name: "qwen2.5-7b-instruct".to_string(),
+            ..AiConfig::default()
+        });
+
+        let status = check_status(&client).await;
+        assert!(status.reachable);
+        assert!(status.model_installed);
+        assert!(status.version.is_none());
+        assert_eq!(server.requests.lock().unwrap()[0].0, "/v1/models");
+    }
+}
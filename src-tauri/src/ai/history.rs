@@ -0,0 +1,313 @@
+use crate::ai::ChatMessage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+#[cfg(test)]
+#[path = "history_test.rs"]
+mod history_test;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub id: String,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub messages: Vec<ChatMessage>,
+    /// Model-written summary of the first `summarized` messages.
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub summarized: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub updated_at: DateTime<Utc>,
+    pub message_count: usize,
+}
+
+/// Chat sessions persisted as one JSON file each under
+/// `<app data dir>/conversations`.
+pub struct ConversationStore {
+    dir: PathBuf,
+    active: Mutex<Option<String>>,
+    /// Held across every load-modify-save so concurrent turns don't drop
+    /// each other's writes.
+    writes: Mutex<()>,
+}
+
+impl ConversationStore {
+    /// Open the store in `dir`, resuming the most recently updated session.
+    pub fn new(dir: PathBuf) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        let store = Self {
+            dir,
+            active: Mutex::new(None),
+            writes: Mutex::new(()),
+        };
+        let latest = store.list()?.into_iter().next().map(|c| c.id);
+        *store.active.lock().unwrap() = latest;
+        Ok(store)
+    }
+
+    pub fn from_app(app: &AppHandle) -> anyhow::Result<Self> {
+        Self::new(app.path().app_data_dir()?.join("conversations"))
+    }
+
+    fn path(&self, id: &str) -> anyhow::Result<PathBuf> {
+        // Ids become file names, so refuse anything that could escape the dir.
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(anyhow::anyhow!("Invalid conversation id: {}", id));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    /// All conversations, most recently updated first.
+    pub fn list(&self) -> anyhow::Result<Vec<ConversationSummary>> {
+        let mut summaries = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|s| Ok(serde_json::from_str::<Conversation>(&s)?))
+            {
+                Ok(c) => summaries.push(ConversationSummary {
+                    id: c.id,
+                    title: c.title,
+                    updated_at: c.updated_at,
+                    message_count: c.messages.len(),
+                }),
+                Err(e) => tracing::warn!("Skipping unreadable conversation {:?}: {}", path, e),
+            }
+        }
+        summaries.sort_by_key(|c| std::cmp::Reverse(c.updated_at));
+        Ok(summaries)
+    }
+
+    pub fn load(&self, id: &str) -> anyhow::Result<Conversation> {
+        let content = std::fs::read_to_string(self.path(id)?)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn save(&self, conversation: &Conversation) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(conversation)?;
+        std::fs::write(self.path(&conversation.id)?, json)?;
+        Ok(())
+    }
+
+    /// Start a fresh session and make it the active one.
+    pub fn create(&self) -> anyhow::Result<Conversation> {
+        let now = Utc::now();
+        let mut id = now.format("%Y%m%d-%H%M%S-%3f").to_string();
+        while self.path(&id)?.exists() {
+            id.push('a');
+        }
+        let conversation = Conversation {
+            id: id.clone(),
+            title: "New conversation".to_string(),
+            created_at: now,
+            updated_at: now,
+            messages: Vec::new(),
+            summary: None,
+            summarized: 0,
+        };
+        self.save(&conversation)?;
+        *self.active.lock().unwrap() = Some(id);
+        Ok(conversation)
+    }
+
+    pub fn active_id(&self) -> Option<String> {
+        self.active.lock().unwrap().clone()
+    }
+
+    pub fn set_active(&self, id: &str) -> anyhow::Result<Conversation> {
+        let conversation = self.load(id)?;
+        *self.active.lock().unwrap() = Some(conversation.id.clone());
+        Ok(conversation)
+    }
+
+    /// The active session, created on first use.
+    pub fn active(&self) -> anyhow::Result<Conversation> {
+        match self.active_id() {
+            Some(id) => match self.load(&id) {
+                Ok(c) => Ok(c),
+                Err(_) => self.create(),
+            },
+            None => self.create(),
+        }
+    }
+
+    /// The running summary, the messages not yet folded into it and the
+    /// index of the first of those.
+    pub fn context(&self, id: &str) -> anyhow::Result<(Option<String>, Vec<ChatMessage>, usize)> {
+        let conversation = self.load(id)?;
+        let start = conversation.summarized.min(conversation.messages.len());
+        Ok((
+            conversation.summary,
+            conversation.messages[start..].to_vec(),
+            start,
+        ))
+    }
+
+    /// Record that `summary` covers the first `covered` messages. A summary
+    /// covering no more than the stored one is dropped: an overlapping
+    /// request already folded those turns.
+    pub fn fold_into_summary(
+        &self,
+        id: &str,
+        summary: Option<String>,
+        covered: usize,
+    ) -> anyhow::Result<()> {
+        let _writes = self.writes.lock().unwrap();
+        let mut conversation = self.load(id)?;
+        let covered = covered.min(conversation.messages.len());
+        if covered <= conversation.summarized {
+            return Ok(());
+        }
+        conversation.summary = summary;
+        conversation.summarized = covered;
+        self.save(&conversation)
+    }
+
+    /// Append one exchange to a session.
+    pub fn append_turn(&self, id: &str, user: &str, assistant: &str) -> anyhow::Result<()> {
+        let _writes = self.writes.lock().unwrap();
+        let mut conversation = self.load(id)?;
+        if conversation.messages.is_empty() {
+            conversation.title = make_title(user);
+        }
+        conversation.messages.push(ChatMessage {
+            role: "user".to_string(),
+            content: user.to_string(),
+        });
+        conversation.messages.push(ChatMessage {
+            role: "assistant".to_string(),
+            content: assistant.to_string(),
+        });
+        conversation.updated_at = Utc::now();
+        self.save(&conversation)
+    }
+
+    /// Drop all messages from a session but keep the session itself.
+    pub fn clear(&self, id: &str) -> anyhow::Result<()> {
+        let _writes = self.writes.lock().unwrap();
+        let mut conversation = self.load(id)?;
+        conversation.messages.clear();
+        conversation.summary = None;
+        conversation.summarized = 0;
+        conversation.title = "New conversation".to_string();
+        conversation.updated_at = Utc::now();
+        self.save(&conversation)
+    }
+
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        std::fs::remove_file(self.path(id)?)?;
+        let mut active = self.active.lock().unwrap();
+        if active.as_deref() == Some(id) {
+            *active = None;
+        }
+        Ok(())
+    }
+
+    /// Render a session as `"markdown"` or `"json"`.
+    pub fn export(&self, id: &str, format: &str) -> anyhow::Result<String> {
+        let conversation = self.load(id)?;
+        match format {
+            "json" => Ok(serde_json::to_string_pretty(&conversation)?),
+            "markdown" | "md" => Ok(to_markdown(&conversation)),
+            other => Err(anyhow::anyhow!("Unsupported export format: {}", other)),
+        }
+    }
+}
+
+fn make_title(first_message: &str) -> String {
+    // Skip the tag line of fenced clipboard text.
+    let line = first_message
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.starts_with(crate::ai::redact::FENCE_START))
+        .unwrap_or_default();
+    let title: String = line.chars().take(40).collect();
+    if title.is_empty() {
+        "New conversation".to_string()
+    } else if line.chars().count() > 40 {
+        format!("{}…", title)
+    } else {
+        title
+    }
+}
+
+fn to_markdown(conversation: &Conversation) -> String {
+    let mut out = format!(
+        "# {}\n\n_Started {}_\n",
+        conversation.title,
+        conversation.created_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    for message in &conversation.messages {
+        let speaker = match message.role.as_str() {
+            "user" => "You",
+            "assistant" => "Ethereal",
+            other => other,
+        };
+        out.push_str(&format!("\n**{}:** {}\n", speaker, message.content));
+    }
+    out
+}
+
+#[tauri::command]
+pub fn list_conversations(
+    store: State<'_, ConversationStore>,
+) -> Result<Vec<ConversationSummary>, String> {
+    store.list().map_err(|e| e.to_string())
+}
+
+/// Load a session and make it the one new messages are appended to.
+#[tauri::command]
+pub fn load_conversation(
+    store: State<'_, ConversationStore>,
+    id: String,
+) -> Result<Conversation, String> {
+    store.set_active(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn new_conversation(store: State<'_, ConversationStore>) -> Result<Conversation, String> {
+    store.create().map_err(|e| e.to_string())
+}
+
+/// Clear the given session (or the active one). With `delete`, remove it entirely.
+#[tauri::command]
+pub fn clear_conversation(
+    store: State<'_, ConversationStore>,
+    id: Option<String>,
+    delete: Option<bool>,
+) -> Result<(), String> {
+    let Some(id) = id.or_else(|| store.active_id()) else {
+        return Ok(());
+    };
+    if delete.unwrap_or(false) {
+        store.delete(&id)
+    } else {
+        store.clear(&id)
+    }
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn export_conversation(
+    store: State<'_, ConversationStore>,
+    id: String,
+    format: Option<String>,
+) -> Result<String, String> {
+    store
+        .export(&id, format.as_deref().unwrap_or("markdown"))
+        .map_err(|e| e.to_string())
+}
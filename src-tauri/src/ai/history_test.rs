@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::history::ConversationStore;
+    use std::path::PathBuf;
+
+    fn temp_store_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ethereal-history-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_active_conversation_created_on_first_use() {
+        let dir = temp_store_dir("create");
+        let store = ConversationStore::new(dir.clone()).unwrap();
+        assert!(store.active_id().is_none());
+
+        let conversation = store.active().unwrap();
+        assert_eq!(store.active_id(), Some(conversation.id.clone()));
+        assert!(conversation.messages.is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_turns_persist_across_restarts() {
+        let dir = temp_store_dir("persist");
+        let id = {
+            let store = ConversationStore::new(dir.clone()).unwrap();
+            let id = store.active().unwrap().id;
+            store
+                .append_turn(&id, "Who are you?", "A spirit in the code.")
+                .unwrap();
+            id
+        };
+
+        let reopened = ConversationStore::new(dir.clone()).unwrap();
+        assert_eq!(reopened.active_id(), Some(id.clone()));
+
+        let conversation = reopened.load(&id).unwrap();
+        assert_eq!(conversation.title, "Who are you?");
+        assert_eq!(conversation.messages.len(), 2);
+        assert_eq!(conversation.messages[1].role, "assistant");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_context_skips_summarized_messages() {
+        let dir = temp_store_dir("summary");
+        let store = ConversationStore::new(dir.clone()).unwrap();
+        let id = store.active().unwrap().id;
+        for i in 0..3 {
+            store
+                .append_turn(&id, &format!("q{}", i), &format!("a{}", i))
+                .unwrap();
+        }
+
+        store
+            .fold_into_summary(&id, Some("They said hi.".to_string()), 4)
+            .unwrap();
+
+        let (summary, messages, start) = store.context(&id).unwrap();
+        assert_eq!(summary.as_deref(), Some("They said hi."));
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "q2");
+        assert_eq!(start, 4);
+
+        store.clear(&id).unwrap();
+        let (summary, messages, _) = store.context(&id).unwrap();
+        assert!(summary.is_none());
+        assert!(messages.is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_overlapping_folds_count_turns_once() {
+        let dir = temp_store_dir("overlap");
+        let store = ConversationStore::new(dir.clone()).unwrap();
+        let id = store.active().unwrap().id;
+        for i in 0..4 {
+            store
+                .append_turn(&id, &format!("q{}", i), &format!("a{}", i))
+                .unwrap();
+        }
+
+        // Two requests read the same snapshot and each fold its first 4.
+        let (_, _, start) = store.context(&id).unwrap();
+        store
+            .fold_into_summary(&id, Some("first".to_string()), start + 4)
+            .unwrap();
+        store
+            .fold_into_summary(&id, Some("second".to_string()), start + 4)
+            .unwrap();
+
+        let (summary, messages, start) = store.context(&id).unwrap();
+        assert_eq!(start, 4);
+        assert_eq!(summary.as_deref(), Some("first"));
+        assert_eq!(messages[0].content, "q2");
+
+        // An older, shorter summary never rolls the count back.
+        store
+            .fold_into_summary(&id, Some("stale".to_string()), 2)
+            .unwrap();
+        assert_eq!(store.load(&id).unwrap().summarized, 4);
+        assert_eq!(store.load(&id).unwrap().summary.as_deref(), Some("first"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_clear_delete_and_export() {
+        let dir = temp_store_dir("export");
+        let store = ConversationStore::new(dir.clone()).unwrap();
+        let id = store.active().unwrap().id;
+        store.append_turn(&id, "ping", "pong").unwrap();
+
+        let markdown = store.export(&id, "markdown").unwrap();
+        assert!(markdown.contains("**You:** ping"));
+        assert!(markdown.contains("**Ethereal:** pong"));
+        assert!(store.export(&id, "json").unwrap().contains("\"pong\""));
+        assert!(store.export(&id, "pdf").is_err());
+
+        store.clear(&id).unwrap();
+        assert!(store.load(&id).unwrap().messages.is_empty());
+
+        store.delete(&id).unwrap();
+        assert!(store.list().unwrap().is_empty());
+        assert!(store.active_id().is_none());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_rejects_path_traversal_ids() {
+        let dir = temp_store_dir("traversal");
+        let store = ConversationStore::new(dir.clone()).unwrap();
+        assert!(store.load("../config").is_err());
+        assert!(store.delete("..").is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_concurrent_turns_are_all_kept() {
+        let dir = temp_store_dir("concurrent");
+        let store = ConversationStore::new(dir.clone()).unwrap();
+        let id = store.active().unwrap().id;
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let (store, id) = (&store, &id);
+                scope.spawn(move || {
+                    store
+                        .append_turn(id, &format!("question {}", i), "answer")
+                        .unwrap();
+                });
+            }
+        });
+        assert_eq!(store.load(&id).unwrap().messages.len(), 16);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_title_skips_fence_tag() {
+        let dir = temp_store_dir("fenced-title");
+        let store = ConversationStore::new(dir.clone()).unwrap();
+        let id = store.active().unwrap().id;
+        let copied = crate::ai::redact::fence("clipboard", "cargo build fails");
+        store.append_turn(&id, &copied, "Let's look.").unwrap();
+        assert_eq!(store.load(&id).unwrap().title, "cargo build fails");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
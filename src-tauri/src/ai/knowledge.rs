@@ -0,0 +1,527 @@
+use crate::ai::{redact::fence, AiError, AiResult, OllamaClient};
+use crate::config::{AppConfig, KnowledgeConfig};
+use futures::future::BoxFuture;
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+#[cfg(test)]
+#[path = "knowledge_test.rs"]
+mod knowledge_test;
+
+/// File types indexed as notes.
+const EXTENSIONS: &[&str] = &["md", "markdown", "txt"];
+
+/// Larger files are skipped; they are rarely notes.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Turns text into an embedding vector.
+pub trait Embedder: Send + Sync {
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, AiResult<Vec<f32>>>;
+}
+
+/// Embeddings from Ollama's `/api/embeddings`.
+pub struct OllamaEmbedder {
+    client: OllamaClient,
+    model: String,
+}
+
+impl OllamaEmbedder {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let mut ai = config.ai.clone();
+        if let Some(endpoint) = &config.knowledge.endpoint {
+            ai.api_endpoint = endpoint.clone();
+        }
+        Self {
+            client: OllamaClient::new(ai),
+            model: config.knowledge.embedding_model.clone(),
+        }
+    }
+}
+
+impl Embedder for OllamaEmbedder {
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, AiResult<Vec<f32>>> {
+        Box::pin(self.client.embed(&self.model, text))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chunk {
+    pub text: String,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    /// Modification time (unix seconds) and size when indexed.
+    modified: u64,
+    len: u64,
+    chunks: Vec<Chunk>,
+}
+
+/// Embedded chunks of every note file, keyed by path relative to the
+/// notes folder. Stored as JSON so an unchanged file is never re-embedded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnowledgeIndex {
+    /// Notes folder (canonicalized), model and chunk size the vectors were
+    /// made with; changing any of them invalidates them all.
+    #[serde(default)]
+    root: PathBuf,
+    model: String,
+    chunk_chars: usize,
+    files: BTreeMap<String, IndexedFile>,
+}
+
+/// Outcome of [`KnowledgeIndex::sync`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SyncReport {
+    pub indexed: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    /// Chunks in the index afterwards.
+    pub chunks: usize,
+}
+
+/// A chunk matching a query.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snippet {
+    pub path: String,
+    pub text: String,
+    pub score: f32,
+}
+
+impl KnowledgeIndex {
+    /// Read the index at `path`; a missing or unreadable one starts empty.
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        match std::fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|s| Ok(serde_json::from_str(&s)?))
+        {
+            Ok(index) => index,
+            Err(e) => {
+                tracing::warn!("Rebuilding knowledge index {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Write then rename, so a crash never leaves half an index behind.
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string(self)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.files.values().map(|f| f.chunks.len()).sum()
+    }
+
+    /// Bring the index in line with the note files under `root`: embed new
+    /// and modified files, forget deleted ones.
+    pub async fn sync(
+        &mut self,
+        root: &Path,
+        embedder: &dyn Embedder,
+        model: &str,
+        chunk_chars: usize,
+    ) -> AiResult<SyncReport> {
+        if !root.is_dir() {
+            return Err(AiError::other(format!(
+                "Notes folder {} does not exist",
+                root.display()
+            )));
+        }
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        if self.root != root || self.model != model || self.chunk_chars != chunk_chars {
+            self.files.clear();
+            self.root = root.clone();
+            self.model = model.to_string();
+            self.chunk_chars = chunk_chars;
+        }
+
+        let mut report = SyncReport::default();
+        let found = note_files(&root).map_err(|e| AiError::other(e.to_string()))?;
+
+        let before = self.files.len();
+        self.files
+            .retain(|name, _| found.iter().any(|f| &f.name == name));
+        report.removed = before - self.files.len();
+
+        for file in found {
+            if self
+                .files
+                .get(&file.name)
+                .is_some_and(|f| f.modified == file.modified && f.len == file.len)
+            {
+                report.unchanged += 1;
+                continue;
+            }
+            let text = match std::fs::read_to_string(&file.path) {
+                Ok(text) => text,
+                Err(e) => {
+                    tracing::warn!("Skipping note {}: {}", file.path.display(), e);
+                    continue;
+                }
+            };
+            let mut chunks = Vec::new();
+            for text in chunk_text(&text, chunk_chars) {
+                let embedding = embedder.embed(&text).await?;
+                chunks.push(Chunk { text, embedding });
+            }
+            self.files.insert(
+                file.name,
+                IndexedFile {
+                    modified: file.modified,
+                    len: file.len,
+                    chunks,
+                },
+            );
+            report.indexed += 1;
+        }
+        report.chunks = self.chunk_count();
+        Ok(report)
+    }
+
+    /// The `k` chunks most similar to `query`, best first, leaving out any
+    /// scoring below `min_score`.
+    pub fn search(&self, query: &[f32], k: usize, min_score: f32) -> Vec<Snippet> {
+        let mut hits: Vec<Snippet> = self
+            .files
+            .iter()
+            .flat_map(|(path, file)| {
+                file.chunks.iter().map(move |chunk| Snippet {
+                    path: path.clone(),
+                    text: chunk.text.clone(),
+                    score: cosine(query, &chunk.embedding),
+                })
+            })
+            .filter(|hit| hit.score >= min_score)
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(k);
+        hits
+    }
+}
+
+struct NoteFile {
+    name: String,
+    path: PathBuf,
+    modified: u64,
+    len: u64,
+}
+
+/// Note files under `root`, skipping hidden entries, sorted by name.
+fn note_files(root: &Path) -> std::io::Result<Vec<NoteFile>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let meta = entry.metadata()?;
+            if meta.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let is_note = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| EXTENSIONS.contains(&e.to_lowercase().as_str()));
+            if !is_note || meta.len() > MAX_FILE_BYTES {
+                continue;
+            }
+            let name = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let modified = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            files.push(NoteFile {
+                name,
+                path,
+                modified,
+                len: meta.len(),
+            });
+        }
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(files)
+}
+
+/// Split `text` into chunks of about `max_chars`, breaking at blank lines.
+/// Paragraphs longer than that are cut at whitespace.
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    let paragraphs = text.split("\n\n").map(str::trim).filter(|p| !p.is_empty());
+    for paragraph in paragraphs {
+        for piece in split_long(paragraph, max_chars) {
+            if !current.is_empty()
+                && current.chars().count() + piece.chars().count() + 2 > max_chars
+            {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(piece);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn split_long(paragraph: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = paragraph;
+    while rest.chars().count() > max_chars {
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let cut = if rest[limit..].starts_with(char::is_whitespace) {
+            limit
+        } else {
+            match rest[..limit].rfind(char::is_whitespace) {
+                Some(i) if i > 0 => i,
+                _ => limit,
+            }
+        };
+        pieces.push(rest[..cut].trim_end());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// `snippets` laid out for the prompt, each fenced as untrusted file
+/// content. `None` when there are none.
+pub fn notes_block(snippets: &[Snippet]) -> Option<String> {
+    if snippets.is_empty() {
+        return None;
+    }
+    let notes: Vec<String> = snippets
+        .iter()
+        .map(|s| fence("notes", &format!("{}:\n{}", s.path, s.text)))
+        .collect();
+    Some(format!(
+        "Notes from the user's files that may help. Answer from them when they \
+         are relevant and mention which file you used:\n\n{}",
+        notes.join("\n\n")
+    ))
+}
+
+/// The knowledge base: the index plus the watcher on the notes folder.
+pub struct KnowledgeBase {
+    index_path: PathBuf,
+    index: tokio::sync::Mutex<KnowledgeIndex>,
+    /// Held for a whole reindex, so two never overlap.
+    syncing: tokio::sync::Mutex<()>,
+    watcher: Mutex<Option<(PathBuf, Debouncer<RecommendedWatcher>)>>,
+}
+
+impl KnowledgeBase {
+    pub fn new(index_path: PathBuf) -> Self {
+        let index = KnowledgeIndex::load(&index_path);
+        Self {
+            index_path,
+            index: tokio::sync::Mutex::new(index),
+            syncing: tokio::sync::Mutex::new(()),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    pub fn from_app(app: &AppHandle) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            app.path()
+                .app_data_dir()?
+                .join("knowledge")
+                .join("index.json"),
+        ))
+    }
+
+    /// Sync the index with `config.directory` and save it. Chats keep
+    /// searching the previous index until the new one is ready.
+    pub async fn reindex(
+        &self,
+        embedder: &dyn Embedder,
+        config: &KnowledgeConfig,
+    ) -> AiResult<SyncReport> {
+        let Some(directory) = config.directory.as_deref() else {
+            return Err(AiError::other("No notes folder configured"));
+        };
+        let _syncing = self.syncing.lock().await;
+
+        let mut index = self.index.lock().await.clone();
+        let result = index
+            .sync(
+                Path::new(directory),
+                embedder,
+                &config.embedding_model,
+                config.chunk_chars,
+            )
+            .await;
+        // Keep what was embedded before a failure.
+        if let Err(e) = index.save(&self.index_path) {
+            tracing::error!("Failed to save knowledge index: {}", e);
+        }
+        *self.index.lock().await = index;
+        result
+    }
+
+    /// Snippets relevant to `query`, per `config.top_k` and `min_score`.
+    pub async fn retrieve(
+        &self,
+        embedder: &dyn Embedder,
+        config: &KnowledgeConfig,
+        query: &str,
+    ) -> AiResult<Vec<Snippet>> {
+        if config.top_k == 0 || self.index.lock().await.chunk_count() == 0 {
+            return Ok(Vec::new());
+        }
+        let query = embedder.embed(query).await?;
+        Ok(self
+            .index
+            .lock()
+            .await
+            .search(&query, config.top_k, config.min_score))
+    }
+
+    /// Watch the configured notes folder, replacing any previous watch.
+    /// Changes trigger a reindex. Stops watching when disabled.
+    fn watch(&self, app: &AppHandle, config: &KnowledgeConfig) {
+        let mut watcher = self.watcher.lock().unwrap();
+        let directory = config
+            .directory
+            .as_deref()
+            .filter(|_| config.enabled)
+            .map(|dir| Path::new(dir).canonicalize().unwrap_or_else(|_| dir.into()));
+        let Some(directory) = directory else {
+            *watcher = None;
+            return;
+        };
+        if watcher.as_ref().is_some_and(|(dir, _)| *dir == directory) {
+            return;
+        }
+
+        let handle = app.clone();
+        let debouncer = new_debouncer(
+            Duration::from_secs(2),
+            move |result: DebounceEventResult| match result {
+                Ok(_events) => spawn_reindex(handle.clone()),
+                Err(e) => tracing::error!("Notes watch error: {:?}", e),
+            },
+        );
+        let mut debouncer = match debouncer {
+            Ok(debouncer) => debouncer,
+            Err(e) => {
+                tracing::error!("Failed to create notes watcher: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = debouncer
+            .watcher()
+            .watch(&directory, RecursiveMode::Recursive)
+        {
+            tracing::error!("Failed to watch notes folder {:?}: {}", directory, e);
+            *watcher = None;
+            return;
+        }
+        tracing::info!("Watching notes folder: {:?}", directory);
+        *watcher = Some((directory, debouncer));
+    }
+}
+
+fn current_config(app: &AppHandle) -> AppConfig {
+    match app.try_state::<crate::config::ConfigState>() {
+        Some(state) => state.0.read().unwrap().clone(),
+        None => AppConfig::load(app).unwrap_or_default(),
+    }
+}
+
+/// Point the watcher at the configured notes folder and bring the index up
+/// to date. Called at startup and whenever the config is reloaded.
+pub fn refresh(app: &AppHandle) {
+    let Some(knowledge) = app.try_state::<KnowledgeBase>() else {
+        return;
+    };
+    let config = current_config(app);
+    knowledge.watch(app, &config.knowledge);
+    if config.knowledge.enabled && config.knowledge.directory.is_some() {
+        spawn_reindex(app.clone());
+    }
+}
+
+async fn reindex_and_report(app: &AppHandle) -> AiResult<SyncReport> {
+    let Some(knowledge) = app.try_state::<KnowledgeBase>() else {
+        return Err(AiError::other("Knowledge base is not available"));
+    };
+    let config = current_config(app);
+    let report = knowledge
+        .reindex(&OllamaEmbedder::from_config(&config), &config.knowledge)
+        .await?;
+    tracing::info!("Knowledge index updated: {:?}", report);
+    if let Err(e) = app.emit("knowledge-indexed", &report) {
+        tracing::error!("Failed to emit knowledge-indexed: {}", e);
+    }
+    Ok(report)
+}
+
+fn spawn_reindex(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = reindex_and_report(&app).await {
+            tracing::warn!("Knowledge indexing failed: {}", e);
+        }
+    });
+}
+
+/// Re-scan the notes folder now and resolve with what changed.
+#[tauri::command]
+pub async fn reindex_knowledge(app: AppHandle) -> Result<SyncReport, AiError> {
+    reindex_and_report(&app).await
+}
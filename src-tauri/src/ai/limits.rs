@@ -0,0 +1,122 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+#[path = "limits_test.rs"]
+mod limits_test;
+
+/// Generation budget handed to the model for a reply of `max_chars` characters.
+///
+/// Assumes roughly four characters per token and doubles it, so the model
+/// has room to finish its sentence before [`trim_reply`] cuts it. `0` means
+/// unlimited.
+pub fn token_limit(max_chars: usize) -> Option<u32> {
+    if max_chars == 0 {
+        return None;
+    }
+    Some(u32::try_from(max_chars.div_ceil(4) * 2).unwrap_or(u32::MAX))
+}
+
+/// Shorten `reply` to at most `max_chars` characters, preferring to cut at
+/// the end of a sentence. Falls back to a word boundary plus an ellipsis.
+pub fn trim_reply(reply: &str, max_chars: usize) -> String {
+    let reply = reply.trim();
+    if max_chars == 0 || reply.chars().count() <= max_chars {
+        return reply.to_string();
+    }
+
+    let cut = reply
+        .char_indices()
+        .nth(max_chars)
+        .map(|(i, _)| i)
+        .unwrap_or(reply.len());
+    let head = &reply[..cut];
+
+    let sentence_end = head
+        .char_indices()
+        .rev()
+        .find(|(_, c)| matches!(c, '.' | '!' | '?' | '。' | '！' | '？' | '…'))
+        .map(|(i, c)| i + c.len_utf8());
+    if let Some(end) = sentence_end {
+        return head[..end].to_string();
+    }
+
+    // Leave room for the ellipsis so the result stays within the limit.
+    let head = match head.char_indices().next_back() {
+        Some((i, _)) => &head[..i],
+        None => head,
+    };
+    let head = match head.rfind(char::is_whitespace) {
+        Some(i) if i > 0 => &head[..i],
+        _ => head,
+    };
+    format!("{}…", head.trim_end())
+}
+
+/// Enforces `AiConfig.cooldown_seconds` between chat requests.
+#[derive(Default)]
+pub struct ChatCooldown {
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl ChatCooldown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a request at `now` unless the previous one is still inside
+    /// `window`, in which case the remaining whole seconds are returned.
+    pub fn try_acquire(&self, window: Duration, now: Instant) -> Result<(), u64> {
+        let mut last = self.last_request.lock().unwrap();
+        if let Some(prev) = *last {
+            let elapsed = now.saturating_duration_since(prev);
+            if elapsed < window {
+                let remaining = window - elapsed;
+                return Err(remaining.as_secs_f64().ceil() as u64);
+            }
+        }
+        *last = Some(now);
+        Ok(())
+    }
+
+    /// Like [`try_acquire`](Self::try_acquire), but the request only counts
+    /// once the returned permit is kept; dropping it (on an error or
+    /// cancellation) gives the slot back.
+    pub fn acquire(&self, window: Duration, now: Instant) -> Result<CooldownPermit<'_>, u64> {
+        self.try_acquire(window, now)?;
+        Ok(CooldownPermit {
+            cooldown: self,
+            at: now,
+            kept: false,
+        })
+    }
+
+    fn release(&self, at: Instant) {
+        let mut last = self.last_request.lock().unwrap();
+        if *last == Some(at) {
+            *last = None;
+        }
+    }
+}
+
+/// A chat request holding the cooldown, see [`ChatCooldown::acquire`].
+pub struct CooldownPermit<'a> {
+    cooldown: &'a ChatCooldown,
+    at: Instant,
+    kept: bool,
+}
+
+impl CooldownPermit<'_> {
+    /// The request succeeded: start the cooldown from when it began.
+    pub fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for CooldownPermit<'_> {
+    fn drop(&mut self) {
+        if !self.kept {
+            self.cooldown.release(self.at);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::limits::{token_limit, trim_reply, ChatCooldown};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_token_limit() {
+        assert_eq!(token_limit(0), None);
+        assert_eq!(token_limit(100), Some(50));
+        assert_eq!(token_limit(10), Some(6));
+    }
+
+    #[test]
+    fn test_trim_reply_short_is_untouched() {
+        assert_eq!(trim_reply("  Hello there.  ", 100), "Hello there.");
+        assert_eq!(trim_reply("Anything goes", 0), "Anything goes");
+    }
+
+    #[test]
+    fn test_trim_reply_cuts_at_sentence_end() {
+        let reply = "I live in the code. The fans hum a lullaby for me tonight.";
+        assert_eq!(trim_reply(reply, 30), "I live in the code.");
+    }
+
+    #[test]
+    fn test_trim_reply_falls_back_to_word_boundary() {
+        let reply = "a very long rambling reply without any punctuation at all";
+        let trimmed = trim_reply(reply, 20);
+        assert_eq!(trimmed, "a very long…");
+        assert!(trimmed.chars().count() <= 20);
+    }
+
+    #[test]
+    fn test_trim_reply_handles_multibyte() {
+        let reply = "我住在代码里。风扇为我唱着摇篮曲，一直到天亮";
+        assert_eq!(trim_reply(reply, 10), "我住在代码里。");
+    }
+
+    #[test]
+    fn test_cooldown_rejects_within_window() {
+        let cooldown = ChatCooldown::new();
+        let start = Instant::now();
+        let window = Duration::from_secs(30);
+
+        assert!(cooldown.try_acquire(window, start).is_ok());
+        assert_eq!(
+            cooldown.try_acquire(window, start + Duration::from_secs(10)),
+            Err(20)
+        );
+        assert!(cooldown
+            .try_acquire(window, start + Duration::from_secs(30))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_cooldown_disabled_with_zero_window() {
+        let cooldown = ChatCooldown::new();
+        let now = Instant::now();
+        assert!(cooldown.try_acquire(Duration::ZERO, now).is_ok());
+        assert!(cooldown.try_acquire(Duration::ZERO, now).is_ok());
+    }
+
+    #[test]
+    fn test_dropped_permit_releases_cooldown() {
+        let cooldown = ChatCooldown::new();
+        let start = Instant::now();
+        let window = Duration::from_secs(30);
+
+        // A failed request doesn't lock the user out...
+        drop(cooldown.acquire(window, start).unwrap());
+        let later = start + Duration::from_secs(1);
+        let permit = cooldown.acquire(window, later).unwrap();
+        // ...while one in flight still blocks concurrent requests.
+        assert!(cooldown.acquire(window, later).is_err());
+        permit.keep();
+        assert_eq!(
+            cooldown
+                .try_acquire(window, start + Duration::from_secs(11))
+                .err(),
+            Some(20)
+        );
+    }
+}
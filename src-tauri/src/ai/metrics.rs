@@ -0,0 +1,426 @@
+use crate::ai::AiResult;
+use chrono::{DateTime, Local, NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+#[cfg(test)]
+#[path = "metrics_test.rs"]
+mod metrics_test;
+
+/// Requests kept in the rolling window.
+pub const WINDOW: usize = 200;
+
+/// Daily aggregates older than this are dropped.
+const KEEP_DAYS: i64 = 90;
+
+/// Requests recorded between writes of the daily aggregates. A new day is
+/// always written straight away.
+const SAVE_EVERY: usize = 10;
+
+/// Token counts a server reported for a request. Servers that don't report
+/// them leave `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub prompt_tokens: Option<u64>,
+    pub eval_tokens: Option<u64>,
+    /// Time the server spent generating `eval_tokens`.
+    pub eval_ms: Option<u64>,
+}
+
+impl Usage {
+    /// Sum of two reports, e.g. of several tool-calling rounds.
+    pub fn merge(self, other: Usage) -> Usage {
+        let add = |a: Option<u64>, b: Option<u64>| match (a, b) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+        };
+        Usage {
+            prompt_tokens: add(self.prompt_tokens, other.prompt_tokens),
+            eval_tokens: add(self.eval_tokens, other.eval_tokens),
+            eval_ms: add(self.eval_ms, other.eval_ms),
+        }
+    }
+}
+
+/// One chat request as seen by a provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestMetrics {
+    pub at: DateTime<Local>,
+    pub provider: String,
+    pub model: String,
+    /// "chat", "stream" or "tools".
+    pub kind: String,
+    /// Until the response started arriving; the first token when streaming.
+    pub ttfb_ms: Option<u64>,
+    pub duration_ms: u64,
+    #[serde(flatten)]
+    pub usage: Usage,
+    /// [`AiError::kind`](crate::ai::AiError::kind) of a failed request.
+    pub error: Option<String>,
+}
+
+/// Totals for one model on one day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyStats {
+    pub date: NaiveDate,
+    pub model: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub total_duration_ms: u64,
+    pub total_ttfb_ms: u64,
+    /// Requests that reported a time to first byte.
+    pub ttfb_samples: u64,
+    pub prompt_tokens: u64,
+    pub eval_tokens: u64,
+    pub eval_ms: u64,
+}
+
+impl DailyStats {
+    fn new(date: NaiveDate, model: &str) -> Self {
+        Self {
+            date,
+            model: model.to_string(),
+            requests: 0,
+            errors: 0,
+            total_duration_ms: 0,
+            total_ttfb_ms: 0,
+            ttfb_samples: 0,
+            prompt_tokens: 0,
+            eval_tokens: 0,
+            eval_ms: 0,
+        }
+    }
+
+    fn add(&mut self, request: &RequestMetrics) {
+        self.requests += 1;
+        self.errors += u64::from(request.error.is_some());
+        self.total_duration_ms += request.duration_ms;
+        if let Some(ttfb) = request.ttfb_ms {
+            self.total_ttfb_ms += ttfb;
+            self.ttfb_samples += 1;
+        }
+        self.prompt_tokens += request.usage.prompt_tokens.unwrap_or(0);
+        self.eval_tokens += request.usage.eval_tokens.unwrap_or(0);
+        self.eval_ms += request.usage.eval_ms.unwrap_or(0);
+    }
+}
+
+/// Rolling-window figures for one model.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelSummary {
+    pub model: String,
+    pub requests: usize,
+    pub errors: usize,
+    pub avg_ttfb_ms: Option<u64>,
+    pub p50_duration_ms: Option<u64>,
+    pub p95_duration_ms: Option<u64>,
+    /// Generation speed, from servers that report eval time.
+    pub tokens_per_sec: Option<f32>,
+}
+
+/// Result of `get_ai_stats`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AiStats {
+    /// Newest first.
+    pub recent: Vec<RequestMetrics>,
+    pub models: Vec<ModelSummary>,
+    /// Oldest first.
+    pub daily: Vec<DailyStats>,
+}
+
+#[derive(Default)]
+struct MetricsState {
+    recent: VecDeque<RequestMetrics>,
+    daily: Vec<DailyStats>,
+    /// Requests not yet written to disk.
+    unsaved: usize,
+    /// Day of the last recorded request.
+    last_date: Option<NaiveDate>,
+    /// Bumped for every snapshot handed to a writer.
+    generation: u64,
+}
+
+/// Per-request chat metrics: a rolling window in memory and daily
+/// aggregates persisted as JSON. Clones share the same data.
+#[derive(Clone, Default)]
+pub struct AiMetrics {
+    state: Arc<Mutex<MetricsState>>,
+    /// `None` keeps everything in memory.
+    path: Option<PathBuf>,
+    /// Generation of the newest snapshot on disk; held while writing so
+    /// writers never interleave or replace newer data with older.
+    saved: Arc<Mutex<u64>>,
+}
+
+impl AiMetrics {
+    /// Metrics persisted to `path`, starting from the aggregates saved there.
+    pub fn new(path: PathBuf) -> Self {
+        let daily = load_daily(&path);
+        Self {
+            state: Arc::new(Mutex::new(MetricsState {
+                last_date: daily.last().map(|d| d.date),
+                daily,
+                ..MetricsState::default()
+            })),
+            path: Some(path),
+            saved: Arc::default(),
+        }
+    }
+
+    pub fn from_app(app: &AppHandle) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            app.path().app_data_dir()?.join("metrics/daily.json"),
+        ))
+    }
+
+    pub fn record(&self, request: RequestMetrics) {
+        let mut state = self.state.lock().unwrap();
+
+        let date = request.at.date_naive();
+        match state
+            .daily
+            .iter_mut()
+            .find(|d| d.date == date && d.model == request.model)
+        {
+            Some(day) => day.add(&request),
+            None => {
+                let mut day = DailyStats::new(date, &request.model);
+                day.add(&request);
+                state.daily.push(day);
+            }
+        }
+        let cutoff = date - TimeDelta::days(KEEP_DAYS);
+        state.daily.retain(|d| d.date > cutoff);
+        state
+            .daily
+            .sort_by(|a, b| (a.date, &a.model).cmp(&(b.date, &b.model)));
+
+        state.recent.push_back(request);
+        while state.recent.len() > WINDOW {
+            state.recent.pop_front();
+        }
+
+        state.unsaved += 1;
+        let new_day = state.last_date.replace(date) != Some(date);
+        if !new_day && state.unsaved < SAVE_EVERY {
+            return;
+        }
+        let Some(snapshot) = self.snapshot(&mut state) else {
+            return;
+        };
+        drop(state);
+
+        // Recording happens on async tasks; keep the file write off them.
+        let metrics = self.clone();
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(move || metrics.write(snapshot));
+            }
+            Err(_) => metrics.write(snapshot),
+        }
+    }
+
+    /// Write any aggregates not yet on disk, e.g. before the app exits.
+    pub fn flush(&self) {
+        let snapshot = self.snapshot(&mut self.state.lock().unwrap());
+        if let Some(snapshot) = snapshot {
+            self.write(snapshot);
+        }
+    }
+
+    fn snapshot(&self, state: &mut MetricsState) -> Option<(u64, Vec<DailyStats>)> {
+        if self.path.is_none() || state.unsaved == 0 {
+            return None;
+        }
+        state.unsaved = 0;
+        state.generation += 1;
+        Some((state.generation, state.daily.clone()))
+    }
+
+    fn write(&self, (generation, daily): (u64, Vec<DailyStats>)) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let mut saved = self.saved.lock().unwrap();
+        if *saved >= generation {
+            return;
+        }
+        match save_daily(path, &daily) {
+            Ok(()) => *saved = generation,
+            Err(e) => tracing::error!("Failed to save AI metrics: {}", e),
+        }
+    }
+
+    pub fn stats(&self) -> AiStats {
+        let state = self.state.lock().unwrap();
+
+        let mut models: Vec<&str> = state.recent.iter().map(|r| r.model.as_str()).collect();
+        models.sort_unstable();
+        models.dedup();
+        let models = models
+            .into_iter()
+            .map(|model| {
+                let requests: Vec<&RequestMetrics> =
+                    state.recent.iter().filter(|r| r.model == model).collect();
+                summarize(model, &requests)
+            })
+            .collect();
+
+        AiStats {
+            recent: state.recent.iter().rev().cloned().collect(),
+            models,
+            daily: state.daily.clone(),
+        }
+    }
+}
+
+fn summarize(model: &str, requests: &[&RequestMetrics]) -> ModelSummary {
+    let ttfbs: Vec<u64> = requests.iter().filter_map(|r| r.ttfb_ms).collect();
+    let mut durations: Vec<u64> = requests
+        .iter()
+        .filter(|r| r.error.is_none())
+        .map(|r| r.duration_ms)
+        .collect();
+    durations.sort_unstable();
+
+    let (tokens, eval_ms) = requests
+        .iter()
+        .filter_map(|r| Some((r.usage.eval_tokens?, r.usage.eval_ms?)))
+        .fold((0, 0), |(t, ms), (rt, rms)| (t + rt, ms + rms));
+
+    ModelSummary {
+        model: model.to_string(),
+        requests: requests.len(),
+        errors: requests.iter().filter(|r| r.error.is_some()).count(),
+        avg_ttfb_ms: (!ttfbs.is_empty()).then(|| ttfbs.iter().sum::<u64>() / ttfbs.len() as u64),
+        p50_duration_ms: percentile(&durations, 0.5),
+        p95_duration_ms: percentile(&durations, 0.95),
+        tokens_per_sec: (eval_ms > 0).then(|| tokens as f32 * 1000.0 / eval_ms as f32),
+    }
+}
+
+/// Nearest-rank percentile of sorted `values`.
+fn percentile(values: &[u64], q: f64) -> Option<u64> {
+    let last = values.len().checked_sub(1)?;
+    Some(values[(last as f64 * q).round() as usize])
+}
+
+fn load_daily(path: &Path) -> Vec<DailyStats> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    match std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|s| Ok(serde_json::from_str(&s)?))
+    {
+        Ok(daily) => daily,
+        Err(e) => {
+            tracing::warn!("Ignoring AI metrics {}: {}", path.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+fn save_daily(path: &Path, daily: &[DailyStats]) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(daily)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+/// Measures one request for [`AiMetrics`]. A timer dropped before
+/// [`finish`](Self::finish) (the request future was aborted) is recorded as
+/// cancelled.
+pub struct RequestTimer {
+    metrics: Option<AiMetrics>,
+    provider: &'static str,
+    model: String,
+    kind: &'static str,
+    at: DateTime<Local>,
+    started: Instant,
+    ttfb: Option<Duration>,
+    usage: Usage,
+    done: bool,
+}
+
+impl RequestTimer {
+    /// Start timing; with `metrics` of `None` nothing is recorded.
+    pub fn start(
+        metrics: Option<&AiMetrics>,
+        provider: &'static str,
+        model: &str,
+        kind: &'static str,
+    ) -> Self {
+        Self {
+            metrics: metrics.cloned(),
+            provider,
+            model: model.to_string(),
+            kind,
+            at: Local::now(),
+            started: Instant::now(),
+            ttfb: None,
+            usage: Usage::default(),
+            done: false,
+        }
+    }
+
+    /// Mark the response as started; later calls are ignored.
+    pub fn first_byte(&mut self) {
+        if self.ttfb.is_none() {
+            self.ttfb = Some(self.started.elapsed());
+        }
+    }
+
+    pub fn add_usage(&mut self, usage: Usage) {
+        self.usage = self.usage.merge(usage);
+    }
+
+    /// Record the request with the outcome of `result`.
+    pub fn finish<T>(mut self, result: &AiResult<T>) {
+        let error = result.as_ref().err().map(|e| e.kind().to_string());
+        self.record(error);
+    }
+
+    /// Drop the measurement, e.g. when the request is handed to another one
+    /// that records itself.
+    pub fn discard(mut self) {
+        self.done = true;
+    }
+
+    fn record(&mut self, error: Option<String>) {
+        self.done = true;
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        metrics.record(RequestMetrics {
+            at: self.at,
+            provider: self.provider.to_string(),
+            model: std::mem::take(&mut self.model),
+            kind: self.kind.to_string(),
+            ttfb_ms: self.ttfb.map(|d| d.as_millis() as u64),
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            usage: self.usage,
+            error,
+        });
+    }
+}
+
+impl Drop for RequestTimer {
+    fn drop(&mut self) {
+        if !self.done {
+            self.record(Some("cancelled".to_string()));
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_ai_stats(metrics: tauri::State<'_, AiMetrics>) -> AiStats {
+    metrics.stats()
+}
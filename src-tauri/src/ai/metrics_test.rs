@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::metrics::{AiMetrics, RequestMetrics, RequestTimer, Usage, SAVE_EVERY, WINDOW};
+    use crate::ai::stub::{serve, StubResponse};
+    use crate::ai::{ChatMessage, ChatProvider, OllamaClient, OpenAiClient};
+    use crate::config::AiConfig;
+    use chrono::{Local, TimeZone};
+
+    fn request(model: &str, duration_ms: u64, error: Option<&str>) -> RequestMetrics {
+        RequestMetrics {
+            at: Local.with_ymd_and_hms(2026, 3, 14, 9, 30, 0).unwrap(),
+            provider: "ollama".to_string(),
+            model: model.to_string(),
+            kind: "chat".to_string(),
+            ttfb_ms: Some(duration_ms / 2),
+            duration_ms,
+            usage: Usage {
+                prompt_tokens: Some(100),
+                eval_tokens: Some(20),
+                eval_ms: Some(500),
+            },
+            error: error.map(str::to_string),
+        }
+    }
+
+    fn config(url: &str) -> AiConfig {
+        AiConfig {
+            api_endpoint: url.to_string(),
+            model_name: "llama3.2".to_string(),
+            max_retries: 0,
+            ..AiConfig::default()
+        }
+    }
+
+    fn hello() -> Vec<ChatMessage> {
+        vec![ChatMessage {
+            role: "user".to_string(),
+            content: "hello".to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_usage_merge_keeps_unknowns() {
+        let known = Usage {
+            prompt_tokens: Some(10),
+            eval_tokens: Some(5),
+            eval_ms: None,
+        };
+        let merged = known.merge(Usage {
+            prompt_tokens: Some(7),
+            eval_tokens: None,
+            eval_ms: None,
+        });
+        assert_eq!(merged.prompt_tokens, Some(17));
+        assert_eq!(merged.eval_tokens, Some(5));
+        assert_eq!(merged.eval_ms, None);
+    }
+
+    #[test]
+    fn test_stats_summarize_window_per_model() {
+        let metrics = AiMetrics::default();
+        for duration in [100, 200, 300, 400, 1000] {
+            metrics.record(request("llama3.2", duration, None));
+        }
+        metrics.record(request("llama3.2", 50, Some("timeout")));
+        metrics.record(request("phi3", 80, None));
+
+        let stats = metrics.stats();
+        assert_eq!(stats.recent.len(), 7);
+        assert_eq!(stats.recent[0].model, "phi3");
+
+        let llama = &stats.models[0];
+        assert_eq!(llama.model, "llama3.2");
+        assert_eq!((llama.requests, llama.errors), (6, 1));
+        // Failed requests don't count towards the durations.
+        assert_eq!(llama.p50_duration_ms, Some(300));
+        assert_eq!(llama.p95_duration_ms, Some(1000));
+        assert_eq!(llama.tokens_per_sec, Some(40.0));
+        assert_eq!(stats.models[1].model, "phi3");
+
+        assert_eq!(stats.daily.len(), 2);
+        assert_eq!(stats.daily[0].requests, 6);
+        assert_eq!(stats.daily[0].errors, 1);
+        assert_eq!(stats.daily[0].eval_tokens, 120);
+    }
+
+    #[test]
+    fn test_window_is_bounded() {
+        let metrics = AiMetrics::default();
+        for _ in 0..WINDOW + 5 {
+            metrics.record(request("m", 10, None));
+        }
+        let stats = metrics.stats();
+        assert_eq!(stats.recent.len(), WINDOW);
+        assert_eq!(stats.daily[0].requests, (WINDOW + 5) as u64);
+    }
+
+    #[test]
+    fn test_daily_aggregates_persist() {
+        let dir = std::env::temp_dir().join(format!("ethereal-metrics-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("metrics/daily.json");
+
+        let metrics = AiMetrics::new(path.clone());
+        metrics.record(request("m", 100, None));
+        metrics.record(request("m", 300, None));
+        metrics.flush();
+
+        let reloaded = AiMetrics::new(path.clone()).stats();
+        assert!(reloaded.recent.is_empty());
+        assert_eq!(reloaded.daily.len(), 1);
+        assert_eq!(reloaded.daily[0].total_duration_ms, 400);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(AiMetrics::new(path).stats().daily.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_daily_aggregates_are_written_in_batches() {
+        let dir =
+            std::env::temp_dir().join(format!("ethereal-metrics-batch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("metrics/daily.json");
+        let saved_requests = || AiMetrics::new(path.clone()).stats().daily[0].requests;
+
+        let metrics = AiMetrics::new(path.clone());
+        metrics.record(request("m", 100, None));
+        assert_eq!(saved_requests(), 1, "a new day is written at once");
+
+        for _ in 0..SAVE_EVERY - 1 {
+            metrics.record(request("m", 100, None));
+        }
+        assert_eq!(saved_requests(), 1);
+        metrics.record(request("m", 100, None));
+        assert_eq!(saved_requests(), SAVE_EVERY as u64 + 1);
+
+        metrics.record(request("m", 100, None));
+        metrics.flush();
+        assert_eq!(saved_requests(), SAVE_EVERY as u64 + 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dropped_timer_counts_as_cancelled() {
+        let metrics = AiMetrics::default();
+        drop(RequestTimer::start(Some(&metrics), "ollama", "m", "chat"));
+        RequestTimer::start(Some(&metrics), "ollama", "m", "tools").discard();
+
+        let stats = metrics.stats();
+        assert_eq!(stats.recent.len(), 1);
+        assert_eq!(stats.recent[0].error.as_deref(), Some("cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_ollama_records_usage_and_errors() {
+        let server = serve(vec![
+            StubResponse::json(
+                r#"{"message":{"role":"assistant","content":"Hi"},"done":true,"prompt_eval_count":26,"eval_count":3,"eval_duration":60000000}"#,
+            ),
+            StubResponse::status(500, "boom"),
+        ]);
+        let metrics = AiMetrics::default();
+        let client = OllamaClient::new(config(&server.url)).with_metrics(metrics.clone());
+
+        client.complete(hello(), Default::default()).await.unwrap();
+        client
+            .complete(hello(), Default::default())
+            .await
+            .unwrap_err();
+
+        let stats = metrics.stats();
+        let failed = &stats.recent[0];
+        assert_eq!(failed.error.as_deref(), Some("http"));
+        let ok = &stats.recent[1];
+        assert_eq!(
+            (ok.provider.as_str(), ok.model.as_str()),
+            ("ollama", "llama3.2")
+        );
+        assert_eq!(ok.kind, "chat");
+        assert!(ok.ttfb_ms.is_some());
+        assert_eq!(ok.usage.prompt_tokens, Some(26));
+        assert_eq!(ok.usage.eval_tokens, Some(3));
+        assert_eq!(ok.usage.eval_ms, Some(60));
+        assert_eq!(ok.error, None);
+    }
+
+    #[tokio::test]
+    async fn test_ollama_stream_takes_usage_from_final_chunk() {
+        let server = serve(vec![StubResponse::ndjson(concat!(
+            r#"{"message":{"role":"assistant","content":"Hel"},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":"lo"},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":""},"done":true,"prompt_eval_count":12,"eval_count":2}"#,
+            "\n",
+        ))]);
+        let metrics = AiMetrics::default();
+        let client = OllamaClient::new(config(&server.url)).with_metrics(metrics.clone());
+
+        let reply = client
+            .complete_stream(hello(), Default::default(), &mut |_| {})
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hello");
+
+        let recorded = &metrics.stats().recent[0];
+        assert_eq!(recorded.kind, "stream");
+        assert_eq!(recorded.usage.prompt_tokens, Some(12));
+        assert_eq!(recorded.usage.eval_tokens, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_openai_records_usage() {
+        let server = serve(vec![StubResponse::json(
+            r#"{"choices":[{"message":{"role":"assistant","content":"Hi"}}],"usage":{"prompt_tokens":9,"completion_tokens":1,"total_tokens":10}}"#,
+        )]);
+        let metrics = AiMetrics::default();
+        let client = OpenAiClient::new(config(&server.url)).with_metrics(metrics.clone());
+
+        client.complete(hello(), Default::default()).await.unwrap();
+
+        let recorded = &metrics.stats().recent[0];
+        assert_eq!(recorded.provider, "openai");
+        assert_eq!(recorded.usage.prompt_tokens, Some(9));
+        assert_eq!(recorded.usage.eval_tokens, Some(1));
+    }
+}
@@ -1,75 +1,203 @@
 use crate::config::AiConfig;
-use reqwest::Client;
+use futures::future::BoxFuture;
+use health::ModelInfo;
+use limits::token_limit;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tools::ToolExecutor;
+
+pub mod actions;
+pub mod cancel;
+pub mod clipboard;
+pub mod commentary;
+pub mod context;
+pub mod error;
+pub mod factory;
+pub mod fallback;
+pub mod health;
+pub mod history;
+pub mod knowledge;
+pub mod limits;
+pub mod metrics;
+pub mod ollama;
+pub mod openai;
+pub mod persona;
+pub mod pull;
+pub mod redact;
+pub mod retry;
+pub mod stream;
+pub mod template;
+pub mod tools;
+
+pub use error::{AiError, AiResult};
+pub use ollama::OllamaClient;
+pub use openai::OpenAiClient;
+
+#[cfg(test)]
+pub(crate) mod stub;
 
 #[cfg(test)]
 #[path = "test.rs"]
 mod test;
 
+/// Deadline of a whole non-streaming request.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Deadline for opening a connection to the AI server.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// HTTP client of the chat backends. Only connecting has a client-wide
+/// deadline: streamed replies may legitimately run for minutes, so each
+/// request sets its own.
+pub(crate) fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
 }
 
-#[derive(Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<ChatMessage>,
-    stream: bool,
+/// Generation parameters of one request. `None` leaves the server's default.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sampling {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
 }
 
-#[derive(Deserialize)]
-struct ChatResponse {
-    message: ChatMessage,
-}
+/// Callback receiving each fragment of a streamed reply.
+pub type TokenSink<'a> = &'a mut (dyn FnMut(&str) + Send);
+
+/// A chat backend speaking some LLM server's HTTP protocol.
+///
+/// Implementations only handle the wire format via [`complete`](Self::complete);
+/// prompt assembly is shared.
+pub trait ChatProvider: Send + Sync {
+    fn config(&self) -> &AiConfig;
+
+    /// Send `messages` as-is and return the model's reply.
+    fn complete<'a>(
+        &'a self,
+        messages: Vec<ChatMessage>,
+        sampling: Sampling,
+    ) -> BoxFuture<'a, AiResult<String>>;
+
+    /// Streaming variant of [`complete`](Self::complete).
+    ///
+    /// `on_token` is called for every content fragment as the model produces
+    /// it; the concatenated reply is returned once the stream ends.
+    fn complete_stream<'a>(
+        &'a self,
+        messages: Vec<ChatMessage>,
+        sampling: Sampling,
+        on_token: TokenSink<'a>,
+    ) -> BoxFuture<'a, AiResult<String>>;
+
+    /// Like [`complete`](Self::complete), but lets the model call `tools`
+    /// and feeds their results back until it produces a text reply.
+    ///
+    /// Providers without tool support ignore `tools`.
+    fn complete_with_tools<'a>(
+        &'a self,
+        messages: Vec<ChatMessage>,
+        sampling: Sampling,
+        tools: &'a dyn ToolExecutor,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        let _ = tools;
+        self.complete(messages, sampling)
+    }
 
-pub struct OllamaClient {
-    client: Client,
-    config: AiConfig,
-}
+    /// Models the server can serve.
+    fn list_models<'a>(&'a self) -> BoxFuture<'a, AiResult<Vec<ModelInfo>>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
 
-impl OllamaClient {
-    pub fn new(config: AiConfig) -> Self {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .unwrap_or_else(|_| Client::new());
-        Self { client, config }
+    /// Server version, for protocols that report one.
+    fn server_version<'a>(&'a self) -> BoxFuture<'a, AiResult<Option<String>>> {
+        Box::pin(async { Ok(None) })
     }
 
-    fn get_mood_modifier(mood_str: &str) -> &'static str {
-        match mood_str {
-            "Happy" => "You are feeling cheerful and helpful.",
-            "Excited" => "You are very energetic and enthusiastic! Use exclamation marks.",
-            "Tired" => "You are exhausted. Use short sentences and sound sleepy.",
-            "Bored" => "You are uninterested and slightly cynical.",
-            "Angry" => "You are irritable and short-tempered.",
-            "Sad" => "You are melancholic and soft-spoken.",
-            "Curious" => "You are inquisitive and eager to learn. Ask follow-up questions.",
-            "Sleeping" => "You are drowsy and barely awake. Use very short, mumbled responses.",
-            _ => "",
+    /// Reply to `history` in persona, with the system prompt prepended.
+    fn chat<'a>(
+        &'a self,
+        history: Vec<ChatMessage>,
+        mood: Option<&'a str>,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        self.complete(self.build_messages(history, mood), self.sampling(mood))
+    }
+
+    /// Streaming variant of [`chat`](Self::chat).
+    fn chat_stream<'a>(
+        &'a self,
+        history: Vec<ChatMessage>,
+        mood: Option<&'a str>,
+        on_token: TokenSink<'a>,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        self.complete_stream(
+            self.build_messages(history, mood),
+            self.sampling(mood),
+            on_token,
+        )
+    }
+
+    /// [`chat`](Self::chat) with tool calling, see
+    /// [`complete_with_tools`](Self::complete_with_tools).
+    fn chat_with_tools<'a>(
+        &'a self,
+        history: Vec<ChatMessage>,
+        mood: Option<&'a str>,
+        tools: &'a dyn ToolExecutor,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        self.complete_with_tools(
+            self.build_messages(history, mood),
+            self.sampling(mood),
+            tools,
+        )
+    }
+
+    /// Sampling for a reply in `mood`, from its [`MoodProfile`]. The token
+    /// budget falls back to the one derived from `max_response_length`.
+    ///
+    /// [`MoodProfile`]: crate::config::MoodProfile
+    fn sampling(&self, mood: Option<&str>) -> Sampling {
+        let config = self.config();
+        let profile = mood.and_then(|m| config.mood_profile(m));
+        Sampling {
+            temperature: profile.and_then(|p| p.temperature),
+            top_p: profile.and_then(|p| p.top_p),
+            max_tokens: profile
+                .and_then(|p| p.max_tokens)
+                .or_else(|| token_limit(config.max_response_length)),
         }
     }
 
-    pub fn build_system_prompt(&self, mood: Option<&str>) -> String {
-        let mut system_prompt = self.config.system_prompt.clone();
+    fn build_system_prompt(&self, mood: Option<&str>) -> String {
+        let config = self.config();
+        let mut system_prompt = config.active_system_prompt().to_string();
 
-        if let Some(m) = mood {
-            let modifier = Self::get_mood_modifier(m);
-            if !modifier.is_empty() {
-                system_prompt = format!("{}\n\nIMPORTANT: {}", system_prompt, modifier);
-            }
+        let modifier = mood
+            .and_then(|m| config.mood_profile(m))
+            .map(|p| p.prompt_modifier.trim())
+            .unwrap_or_default();
+        if !modifier.is_empty() {
+            system_prompt = format!("{}\n\nIMPORTANT: {}", system_prompt, modifier);
         }
         system_prompt
     }
 
-    pub async fn chat(
-        &self,
-        history: Vec<ChatMessage>,
-        mood: Option<&str>,
-    ) -> anyhow::Result<String> {
-        let system_prompt = self.build_system_prompt(mood);
+    fn build_messages(&self, history: Vec<ChatMessage>, mood: Option<&str>) -> Vec<ChatMessage> {
+        let mut system_prompt = self.build_system_prompt(mood);
+        if history
+            .iter()
+            .any(|m| m.content.contains(redact::FENCE_START))
+        {
+            system_prompt = format!("{}\n\n{}", system_prompt, redact::FENCE_NOTICE);
+        }
 
         let mut messages = vec![ChatMessage {
             role: "system".to_string(),
@@ -77,25 +205,6 @@ impl OllamaClient {
         }];
 
         messages.extend(history);
-
-        let request = ChatRequest {
-            model: self.config.model_name.clone(),
-            messages,
-            stream: false,
-        };
-
-        let url = format!("{}/api/chat", self.config.api_endpoint);
-
-        let res = self.client.post(&url).json(&request).send().await?;
-
-        if !res.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "Ollama API returned error: {}",
-                res.status()
-            ));
-        }
-
-        let body: ChatResponse = res.json().await?;
-        Ok(body.message.content)
+        messages
     }
 }
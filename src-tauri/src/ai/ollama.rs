@@ -0,0 +1,541 @@
+use crate::ai::{
+    error::check_response,
+    health::{ModelInfo, PROBE_TIMEOUT},
+    http_client,
+    metrics::{AiMetrics, RequestTimer, Usage},
+    pull::PullProgress,
+    retry::{with_retry, RetryPolicy},
+    stream::{idle_timeout, NdjsonDecoder, STREAM_IDLE_TIMEOUT},
+    tools::{ToolCall, ToolDefinition, ToolExecutor, MAX_TOOL_ROUNDS},
+    AiError, AiResult, ChatMessage, ChatProvider, Sampling, TokenSink, REQUEST_TIMEOUT,
+};
+use crate::config::AiConfig;
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Model downloads can take far longer than a chat request.
+const PULL_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Serialize)]
+struct ChatRequest<'a, M> {
+    model: &'a str,
+    messages: &'a [M],
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<ChatOptions>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tools: &'a [ToolDefinition],
+}
+
+/// Subset of Ollama's model `options` we control.
+#[derive(Serialize)]
+struct ChatOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+}
+
+impl ChatOptions {
+    /// `None` when nothing differs from the model's defaults.
+    fn from_sampling(sampling: Sampling) -> Option<Self> {
+        (sampling != Sampling::default()).then_some(Self {
+            num_predict: sampling.max_tokens,
+            temperature: sampling.temperature,
+            top_p: sampling.top_p,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: ChatMessage,
+    #[serde(flatten)]
+    usage: OllamaUsage,
+}
+
+/// Non-streaming response when tools were offered.
+#[derive(Deserialize)]
+struct ToolChatResponse {
+    message: ToolChatMessage,
+    #[serde(flatten)]
+    usage: OllamaUsage,
+}
+
+/// Counters Ollama adds to a finished response.
+#[derive(Deserialize, Default)]
+struct OllamaUsage {
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+    /// Nanoseconds.
+    #[serde(default)]
+    eval_duration: Option<u64>,
+}
+
+impl From<OllamaUsage> for Usage {
+    fn from(usage: OllamaUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_eval_count,
+            eval_tokens: usage.eval_count,
+            eval_ms: usage.eval_duration.map(|ns| ns / 1_000_000),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ToolChatMessage {
+    #[serde(default)]
+    content: String,
+    /// Kept raw so it can be echoed back verbatim in the assistant turn.
+    #[serde(default)]
+    tool_calls: Vec<Value>,
+}
+
+/// One NDJSON record of a streaming `/api/chat` response.
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    message: Option<ChatMessage>,
+    #[serde(default)]
+    done: bool,
+    /// Set instead of `message` when the server fails mid-reply.
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(flatten)]
+    usage: OllamaUsage,
+}
+
+/// Response of `/api/tags`.
+#[derive(Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<TagModel>,
+}
+
+#[derive(Deserialize)]
+struct TagModel {
+    name: String,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    details: ModelDetails,
+}
+
+#[derive(Deserialize, Default)]
+struct ModelDetails {
+    #[serde(default)]
+    family: Option<String>,
+    #[serde(default)]
+    parameter_size: Option<String>,
+    #[serde(default)]
+    quantization_level: Option<String>,
+}
+
+/// One NDJSON record of a streaming `/api/pull` response.
+#[derive(Deserialize)]
+struct PullChunk {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    digest: Option<String>,
+    #[serde(default)]
+    total: Option<u64>,
+    #[serde(default)]
+    completed: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Response of `/api/embeddings`.
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct VersionResponse {
+    version: String,
+}
+
+/// Client for Ollama's native `/api/chat` endpoint.
+pub struct OllamaClient {
+    client: Client,
+    config: AiConfig,
+    metrics: Option<AiMetrics>,
+}
+
+impl OllamaClient {
+    pub fn new(config: AiConfig) -> Self {
+        Self {
+            client: http_client(),
+            config,
+            metrics: None,
+        }
+    }
+
+    /// Record chat requests into `metrics`.
+    pub fn with_metrics(mut self, metrics: AiMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn timer(&self, kind: &'static str) -> RequestTimer {
+        RequestTimer::start(
+            self.metrics.as_ref(),
+            "ollama",
+            self.config.active_model(),
+            kind,
+        )
+    }
+
+    async fn post<M: Serialize>(
+        &self,
+        messages: &[M],
+        stream: bool,
+        sampling: Sampling,
+        tools: &[ToolDefinition],
+    ) -> AiResult<reqwest::Response> {
+        let request = ChatRequest {
+            model: self.config.active_model(),
+            messages,
+            stream,
+            options: ChatOptions::from_sampling(sampling),
+            tools,
+        };
+
+        let url = format!("{}/api/chat", self.config.api_endpoint);
+
+        let builder = self.client.post(&url).json(&request);
+        if stream {
+            // Only silence counts against a streamed reply, not its length.
+            idle_timeout(STREAM_IDLE_TIMEOUT, builder.send()).await
+        } else {
+            Ok(builder.timeout(REQUEST_TIMEOUT).send().await?)
+        }
+    }
+
+    /// Post a chat request and check its status, retrying retryable
+    /// failures as configured. Only the request itself is retried, never a
+    /// response body that is already being consumed.
+    async fn send<M: Serialize>(
+        &self,
+        messages: &[M],
+        stream: bool,
+        sampling: Sampling,
+        tools: &[ToolDefinition],
+    ) -> AiResult<reqwest::Response> {
+        with_retry(&RetryPolicy::from_config(&self.config), || async {
+            self.check(self.post(messages, stream, sampling, tools).await?)
+                .await
+        })
+        .await
+    }
+
+    async fn check(&self, res: reqwest::Response) -> AiResult<reqwest::Response> {
+        check_response(res, self.config.active_model()).await
+    }
+
+    /// Embed `text` with `model` through `/api/embeddings`.
+    pub async fn embed(&self, model: &str, text: &str) -> AiResult<Vec<f32>> {
+        let url = format!("{}/api/embeddings", self.config.api_endpoint);
+        let response: EmbeddingResponse =
+            with_retry(&RetryPolicy::from_config(&self.config), || {
+                let request = self
+                    .client
+                    .post(&url)
+                    .json(&json!({ "model": model, "prompt": text }))
+                    .timeout(REQUEST_TIMEOUT)
+                    .send();
+                async move { check_response(request.await?, model).await }
+            })
+            .await?
+            .json()
+            .await?;
+        if response.embedding.is_empty() {
+            return Err(AiError::parse(format!(
+                "{} returned an empty embedding",
+                model
+            )));
+        }
+        Ok(response.embedding)
+    }
+
+    /// Download `model` through `/api/pull`, reporting every progress record.
+    ///
+    /// Resolves once Ollama reports success; aborts when `cancel` is notified.
+    pub async fn pull_model(
+        &self,
+        model: &str,
+        cancel: &Notify,
+        on_progress: &mut (dyn FnMut(PullProgress) + Send),
+    ) -> AiResult<()> {
+        let url = format!("{}/api/pull", self.config.api_endpoint);
+        let request = self
+            .client
+            .post(&url)
+            .json(&json!({ "model": model, "stream": true }))
+            .timeout(PULL_TIMEOUT)
+            .send();
+
+        let mut res = tokio::select! {
+            biased;
+            _ = cancel.notified() => return Err(AiError::Cancelled),
+            res = request => check_response(res?, model).await?,
+        };
+
+        let mut decoder = NdjsonDecoder::new();
+        let mut succeeded = false;
+        let mut handle = |chunk: PullChunk| -> AiResult<()> {
+            if let Some(error) = chunk.error {
+                return Err(pull_error(model, &error));
+            }
+            succeeded |= chunk.status == "success";
+            on_progress(PullProgress {
+                model: model.to_string(),
+                status: chunk.status,
+                digest: chunk.digest,
+                completed: chunk.completed,
+                total: chunk.total,
+            });
+            Ok(())
+        };
+
+        loop {
+            let bytes = tokio::select! {
+                biased;
+                _ = cancel.notified() => return Err(AiError::Cancelled),
+                bytes = res.chunk() => bytes?,
+            };
+            let Some(bytes) = bytes else {
+                break;
+            };
+            for chunk in decoder.push::<PullChunk>(&bytes)? {
+                handle(chunk)?;
+            }
+        }
+        if let Some(chunk) = decoder.finish::<PullChunk>()? {
+            handle(chunk)?;
+        }
+
+        if succeeded {
+            Ok(())
+        } else {
+            Err(AiError::other(format!(
+                "Pull of {} ended before completing",
+                model
+            )))
+        }
+    }
+
+    /// The tool-calling loop of `complete_with_tools`. `None` when the model
+    /// doesn't support tools.
+    async fn tool_rounds(
+        &self,
+        messages: &[ChatMessage],
+        sampling: Sampling,
+        tools: &dyn ToolExecutor,
+        timer: &mut RequestTimer,
+    ) -> AiResult<Option<String>> {
+        let definitions = tools.definitions();
+        let mut wire: Vec<Value> = messages.iter().map(|m| json!(m)).collect();
+
+        for round in 0..MAX_TOOL_ROUNDS {
+            let res = match self.send(&wire, false, sampling, &definitions).await {
+                // Models without tool support are rejected with 400.
+                Err(AiError::Http { status: 400, .. }) if round == 0 => return Ok(None),
+                res => res?,
+            };
+            timer.first_byte();
+            let body: ToolChatResponse = res.json().await?;
+            timer.add_usage(body.usage.into());
+            if body.message.tool_calls.is_empty() {
+                return Ok(Some(body.message.content));
+            }
+
+            let calls: Vec<ToolCall> = body
+                .message
+                .tool_calls
+                .iter()
+                .filter_map(ToolCall::from_value)
+                .collect();
+            wire.push(json!({
+                "role": "assistant",
+                "content": body.message.content,
+                "tool_calls": body.message.tool_calls,
+            }));
+            for call in calls {
+                tracing::debug!("Model called tool {}", call.name);
+                wire.push(json!({
+                    "role": "tool",
+                    "tool_name": call.name,
+                    "content": tools.execute(&call.name, &call.arguments),
+                }));
+            }
+        }
+
+        // Out of rounds: ask for a plain answer from what it has gathered.
+        let res = self.send(&wire, false, sampling, &[]).await?;
+        let body: ToolChatResponse = res.json().await?;
+        timer.add_usage(body.usage.into());
+        Ok(Some(body.message.content))
+    }
+
+    async fn get(&self, path: &str) -> AiResult<reqwest::Response> {
+        let url = format!("{}{}", self.config.api_endpoint, path);
+        let res = self.client.get(&url).timeout(PROBE_TIMEOUT).send().await?;
+        self.check(res).await
+    }
+}
+
+/// Classify an `error` record of a pull stream.
+fn pull_error(model: &str, error: &str) -> AiError {
+    if error.contains("file does not exist") || error.contains("not found") {
+        AiError::ModelMissing {
+            model: model.to_string(),
+        }
+    } else {
+        AiError::other(format!("Ollama failed to pull {}: {}", model, error))
+    }
+}
+
+impl ChatProvider for OllamaClient {
+    fn config(&self) -> &AiConfig {
+        &self.config
+    }
+
+    fn complete<'a>(
+        &'a self,
+        messages: Vec<ChatMessage>,
+        sampling: Sampling,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        Box::pin(async move {
+            let mut timer = self.timer("chat");
+            let result = async {
+                let res = self.send(&messages, false, sampling, &[]).await?;
+                timer.first_byte();
+                let body: ChatResponse = res.json().await?;
+                timer.add_usage(body.usage.into());
+                Ok(body.message.content)
+            }
+            .await;
+            timer.finish(&result);
+            result
+        })
+    }
+
+    fn list_models<'a>(&'a self) -> BoxFuture<'a, AiResult<Vec<ModelInfo>>> {
+        Box::pin(async move {
+            let tags: TagsResponse = self.get("/api/tags").await?.json().await?;
+            Ok(tags
+                .models
+                .into_iter()
+                .map(|m| ModelInfo {
+                    name: m.name,
+                    size: m.size,
+                    family: m.details.family.filter(|f| !f.is_empty()),
+                    parameter_size: m.details.parameter_size.filter(|p| !p.is_empty()),
+                    quantization: m.details.quantization_level.filter(|q| !q.is_empty()),
+                })
+                .collect())
+        })
+    }
+
+    fn server_version<'a>(&'a self) -> BoxFuture<'a, AiResult<Option<String>>> {
+        Box::pin(async move {
+            let body: VersionResponse = self.get("/api/version").await?.json().await?;
+            Ok(Some(body.version))
+        })
+    }
+
+    fn complete_with_tools<'a>(
+        &'a self,
+        messages: Vec<ChatMessage>,
+        sampling: Sampling,
+        tools: &'a dyn ToolExecutor,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        Box::pin(async move {
+            let mut timer = self.timer("tools");
+            match self
+                .tool_rounds(&messages, sampling, tools, &mut timer)
+                .await
+            {
+                Ok(None) => {
+                    tracing::warn!(
+                        "Model {} rejected tools, answering without them",
+                        self.config.active_model()
+                    );
+                    // The plain request records itself.
+                    timer.discard();
+                    self.complete(messages, sampling).await
+                }
+                result => {
+                    let result = result.map(Option::unwrap_or_default);
+                    timer.finish(&result);
+                    result
+                }
+            }
+        })
+    }
+
+    fn complete_stream<'a>(
+        &'a self,
+        messages: Vec<ChatMessage>,
+        sampling: Sampling,
+        on_token: TokenSink<'a>,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        Box::pin(async move {
+            let mut timer = self.timer("stream");
+            let result = async {
+                let mut res = self.send(&messages, true, sampling, &[]).await?;
+                let mut decoder = NdjsonDecoder::new();
+                let mut reply = String::new();
+
+                // Returns the usage counters once the final record arrives.
+                let mut handle = |chunk: ChatChunk, reply: &mut String| -> AiResult<_> {
+                    if let Some(error) = chunk.error {
+                        return Err(AiError::other(format!(
+                            "Ollama stopped the reply: {}",
+                            error
+                        )));
+                    }
+                    if let Some(message) = chunk.message {
+                        if !message.content.is_empty() {
+                            on_token(&message.content);
+                            reply.push_str(&message.content);
+                        }
+                    }
+                    Ok(chunk.done.then(|| Usage::from(chunk.usage)))
+                };
+
+                while let Some(bytes) = idle_timeout(STREAM_IDLE_TIMEOUT, res.chunk()).await? {
+                    timer.first_byte();
+                    for chunk in decoder.push::<ChatChunk>(&bytes)? {
+                        if let Some(usage) = handle(chunk, &mut reply)? {
+                            timer.add_usage(usage);
+                            return Ok(reply);
+                        }
+                    }
+                }
+
+                if let Some(chunk) = decoder.finish::<ChatChunk>()? {
+                    if let Some(usage) = handle(chunk, &mut reply)? {
+                        timer.add_usage(usage);
+                    }
+                }
+
+                Ok(reply)
+            }
+            .await;
+            timer.finish(&result);
+            result
+        })
+    }
+}
@@ -0,0 +1,418 @@
+use crate::ai::{
+    error::check_response,
+    health::{ModelInfo, PROBE_TIMEOUT},
+    http_client,
+    metrics::{AiMetrics, RequestTimer, Usage},
+    retry::{with_retry, RetryPolicy},
+    stream::{idle_timeout, parse_sse_line, LineBuffer, SseEvent, STREAM_IDLE_TIMEOUT},
+    tools::{ToolCall, ToolDefinition, ToolExecutor, MAX_TOOL_ROUNDS},
+    AiError, AiResult, ChatMessage, ChatProvider, Sampling, TokenSink, REQUEST_TIMEOUT,
+};
+use crate::config::AiConfig;
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Serialize)]
+struct ChatRequest<'a, M> {
+    model: &'a str,
+    messages: &'a [M],
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tools: &'a [ToolDefinition],
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct OpenAiUsage {
+    #[serde(default)]
+    prompt_tokens: Option<u64>,
+    #[serde(default)]
+    completion_tokens: Option<u64>,
+}
+
+impl From<OpenAiUsage> for Usage {
+    fn from(usage: OpenAiUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_tokens,
+            eval_tokens: usage.completion_tokens,
+            eval_ms: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: ChatMessage,
+}
+
+/// Non-streaming response when tools were offered.
+#[derive(Deserialize)]
+struct ToolChatResponse {
+    choices: Vec<ToolChoice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Deserialize)]
+struct ToolChoice {
+    message: ToolChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ToolChatMessage {
+    /// `null` when the model only calls tools.
+    #[serde(default)]
+    content: Option<String>,
+    /// Kept raw so it can be echoed back verbatim in the assistant turn.
+    #[serde(default)]
+    tool_calls: Vec<Value>,
+}
+
+/// Response of `/v1/models`.
+#[derive(Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+/// One `data:` payload of a streaming completion.
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: Delta,
+}
+
+#[derive(Deserialize, Default)]
+struct Delta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// Client for servers exposing the OpenAI `/v1/chat/completions` protocol
+/// (llama.cpp server, LM Studio, vLLM, ...).
+pub struct OpenAiClient {
+    client: Client,
+    config: AiConfig,
+    metrics: Option<AiMetrics>,
+}
+
+impl OpenAiClient {
+    pub fn new(config: AiConfig) -> Self {
+        Self {
+            client: http_client(),
+            config,
+            metrics: None,
+        }
+    }
+
+    /// Record chat requests into `metrics`.
+    pub fn with_metrics(mut self, metrics: AiMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn timer(&self, kind: &'static str) -> RequestTimer {
+        RequestTimer::start(
+            self.metrics.as_ref(),
+            "openai",
+            self.config.active_model(),
+            kind,
+        )
+    }
+
+    /// Accept endpoints both with and without the `/v1` suffix.
+    fn api_url(&self, path: &str) -> String {
+        let base = self.config.api_endpoint.trim_end_matches('/');
+        if base.ends_with("/v1") {
+            format!("{}{}", base, path)
+        } else {
+            format!("{}/v1{}", base, path)
+        }
+    }
+
+    fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.config.api_key.as_deref().filter(|k| !k.is_empty()) {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    async fn post<M: Serialize>(
+        &self,
+        messages: &[M],
+        stream: bool,
+        sampling: Sampling,
+        tools: &[ToolDefinition],
+    ) -> AiResult<reqwest::Response> {
+        let request = ChatRequest {
+            model: self.config.active_model(),
+            messages,
+            stream,
+            max_tokens: sampling.max_tokens,
+            temperature: sampling.temperature,
+            top_p: sampling.top_p,
+            tools,
+        };
+
+        let builder = self
+            .client
+            .post(self.api_url("/chat/completions"))
+            .json(&request);
+        let builder = self.authorize(builder);
+        if stream {
+            // Only silence counts against a streamed reply, not its length.
+            idle_timeout(STREAM_IDLE_TIMEOUT, builder.send()).await
+        } else {
+            Ok(builder.timeout(REQUEST_TIMEOUT).send().await?)
+        }
+    }
+
+    /// Post a chat request and check its status, retrying retryable
+    /// failures as configured. Only the request itself is retried, never a
+    /// response body that is already being consumed.
+    async fn send<M: Serialize>(
+        &self,
+        messages: &[M],
+        stream: bool,
+        sampling: Sampling,
+        tools: &[ToolDefinition],
+    ) -> AiResult<reqwest::Response> {
+        with_retry(&RetryPolicy::from_config(&self.config), || async {
+            self.check(self.post(messages, stream, sampling, tools).await?)
+                .await
+        })
+        .await
+    }
+
+    /// The tool-calling loop of `complete_with_tools`. `None` when the server
+    /// or model doesn't support tools.
+    async fn tool_rounds(
+        &self,
+        messages: &[ChatMessage],
+        sampling: Sampling,
+        tools: &dyn ToolExecutor,
+        timer: &mut RequestTimer,
+    ) -> AiResult<Option<String>> {
+        let definitions = tools.definitions();
+        let mut wire: Vec<Value> = messages.iter().map(|m| json!(m)).collect();
+
+        for round in 0..MAX_TOOL_ROUNDS {
+            let res = match self.send(&wire, false, sampling, &definitions).await {
+                // Servers or models without tool support reject the request with 400.
+                Err(AiError::Http { status: 400, .. }) if round == 0 => return Ok(None),
+                res => res?,
+            };
+            timer.first_byte();
+            let message = self.first_message(res.json().await?, timer)?;
+            if message.tool_calls.is_empty() {
+                return Ok(Some(message.content.unwrap_or_default()));
+            }
+
+            let calls: Vec<ToolCall> = message
+                .tool_calls
+                .iter()
+                .filter_map(ToolCall::from_value)
+                .collect();
+            wire.push(json!({
+                "role": "assistant",
+                "content": message.content,
+                "tool_calls": message.tool_calls,
+            }));
+            for call in calls {
+                tracing::debug!("Model called tool {}", call.name);
+                wire.push(json!({
+                    "role": "tool",
+                    "tool_call_id": call.id,
+                    "content": tools.execute(&call.name, &call.arguments),
+                }));
+            }
+        }
+
+        // Out of rounds: ask for a plain answer from what it has gathered.
+        let res = self.send(&wire, false, sampling, &[]).await?;
+        Ok(Some(
+            self.first_message(res.json().await?, timer)?
+                .content
+                .unwrap_or_default(),
+        ))
+    }
+
+    async fn check(&self, res: reqwest::Response) -> AiResult<reqwest::Response> {
+        check_response(res, self.config.active_model()).await
+    }
+
+    /// The reply message of a tool-calling response, counting its usage.
+    fn first_message(
+        &self,
+        body: ToolChatResponse,
+        timer: &mut RequestTimer,
+    ) -> AiResult<ToolChatMessage> {
+        timer.add_usage(body.usage.map(Usage::from).unwrap_or_default());
+        body.choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
+            .ok_or_else(|| AiError::parse("Completion response contained no choices"))
+    }
+}
+
+impl ChatProvider for OpenAiClient {
+    fn config(&self) -> &AiConfig {
+        &self.config
+    }
+
+    fn complete<'a>(
+        &'a self,
+        messages: Vec<ChatMessage>,
+        sampling: Sampling,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        Box::pin(async move {
+            let mut timer = self.timer("chat");
+            let result = async {
+                let res = self.send(&messages, false, sampling, &[]).await?;
+                timer.first_byte();
+                let body: ChatResponse = res.json().await?;
+                timer.add_usage(body.usage.map(Usage::from).unwrap_or_default());
+                body.choices
+                    .into_iter()
+                    .next()
+                    .map(|choice| choice.message.content)
+                    .ok_or_else(|| AiError::parse("Completion response contained no choices"))
+            }
+            .await;
+            timer.finish(&result);
+            result
+        })
+    }
+
+    fn list_models<'a>(&'a self) -> BoxFuture<'a, AiResult<Vec<ModelInfo>>> {
+        Box::pin(async move {
+            let builder = self
+                .client
+                .get(self.api_url("/models"))
+                .timeout(PROBE_TIMEOUT);
+            let res = self.check(self.authorize(builder).send().await?).await?;
+            let body: ModelsResponse = res.json().await?;
+            Ok(body
+                .data
+                .into_iter()
+                .map(|m| ModelInfo {
+                    name: m.id,
+                    size: None,
+                    family: None,
+                    parameter_size: None,
+                    quantization: None,
+                })
+                .collect())
+        })
+    }
+
+    fn complete_with_tools<'a>(
+        &'a self,
+        messages: Vec<ChatMessage>,
+        sampling: Sampling,
+        tools: &'a dyn ToolExecutor,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        Box::pin(async move {
+            let mut timer = self.timer("tools");
+            match self
+                .tool_rounds(&messages, sampling, tools, &mut timer)
+                .await
+            {
+                Ok(None) => {
+                    tracing::warn!(
+                        "Model {} rejected tools, answering without them",
+                        self.config.active_model()
+                    );
+                    // The plain request records itself.
+                    timer.discard();
+                    self.complete(messages, sampling).await
+                }
+                result => {
+                    let result = result.map(Option::unwrap_or_default);
+                    timer.finish(&result);
+                    result
+                }
+            }
+        })
+    }
+
+    fn complete_stream<'a>(
+        &'a self,
+        messages: Vec<ChatMessage>,
+        sampling: Sampling,
+        on_token: TokenSink<'a>,
+    ) -> BoxFuture<'a, AiResult<String>> {
+        Box::pin(async move {
+            let mut timer = self.timer("stream");
+            let result = async {
+                let mut res = self.send(&messages, true, sampling, &[]).await?;
+                let mut lines = LineBuffer::new();
+                let mut reply = String::new();
+
+                // Returns true once the server signals the end of the stream.
+                let mut handle = |line: &str, reply: &mut String| -> AiResult<bool> {
+                    match parse_sse_line(line) {
+                        Some(SseEvent::Done) => Ok(true),
+                        Some(SseEvent::Data(data)) => {
+                            let chunk: ChatChunk = serde_json::from_str(&data)?;
+                            for choice in chunk.choices {
+                                if let Some(content) =
+                                    choice.delta.content.filter(|c| !c.is_empty())
+                                {
+                                    on_token(&content);
+                                    reply.push_str(&content);
+                                }
+                            }
+                            Ok(false)
+                        }
+                        None => Ok(false),
+                    }
+                };
+
+                while let Some(bytes) = idle_timeout(STREAM_IDLE_TIMEOUT, res.chunk()).await? {
+                    timer.first_byte();
+                    for line in lines.push(&bytes) {
+                        if handle(&line, &mut reply)? {
+                            return Ok(reply);
+                        }
+                    }
+                }
+
+                if let Some(line) = lines.finish() {
+                    handle(&line, &mut reply)?;
+                }
+
+                Ok(reply)
+            }
+            .await;
+            timer.finish(&result);
+            result
+        })
+    }
+}
@@ -0,0 +1,76 @@
+use crate::config::{AiConfig, AppConfig};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+#[cfg(test)]
+#[path = "persona_test.rs"]
+mod persona_test;
+
+/// A persona as listed to the frontend.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PersonaInfo {
+    pub id: String,
+    pub name: String,
+    /// Model the persona chats with.
+    pub model: String,
+    pub sprite_path: Option<String>,
+    pub active: bool,
+}
+
+/// All configured personas, ordered by id.
+pub fn persona_infos(ai: &AiConfig) -> Vec<PersonaInfo> {
+    ai.personas
+        .iter()
+        .map(|(id, persona)| PersonaInfo {
+            id: id.clone(),
+            name: if persona.name.is_empty() {
+                id.clone()
+            } else {
+                persona.name.clone()
+            },
+            model: persona
+                .model_name
+                .clone()
+                .filter(|m| !m.is_empty())
+                .unwrap_or_else(|| ai.model_name.clone()),
+            sprite_path: persona.sprite_path.clone(),
+            active: ai.active_persona.as_deref() == Some(id.as_str()),
+        })
+        .collect()
+}
+
+/// Make `id` the active persona, or go back to the base settings with `None`.
+pub fn select_persona(ai: &mut AiConfig, id: Option<String>) -> anyhow::Result<()> {
+    if let Some(id) = &id {
+        if !ai.personas.contains_key(id) {
+            anyhow::bail!("Unknown persona '{}'", id);
+        }
+    }
+    ai.active_persona = id;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_personas(app: AppHandle) -> Result<Vec<PersonaInfo>, String> {
+    let config = AppConfig::load(&app).map_err(|e| e.to_string())?;
+    Ok(persona_infos(&config.ai))
+}
+
+/// Switch to persona `id` (`None` for the base settings), save the config and
+/// broadcast it.
+#[tauri::command]
+pub fn switch_persona(app: AppHandle, id: Option<String>) -> Result<AppConfig, String> {
+    let mut config = AppConfig::load(&app).map_err(|e| e.to_string())?;
+    select_persona(&mut config.ai, id).map_err(|e| e.to_string())?;
+    config.save(&app).map_err(|e| e.to_string())?;
+    tracing::info!(
+        "Switched persona to {}",
+        config.ai.active_persona.as_deref().unwrap_or("(default)")
+    );
+
+    let config = config.masked();
+    if let Err(e) = app.emit("config-updated", &config) {
+        tracing::error!("Failed to emit config-updated: {}", e);
+    }
+    Ok(config)
+}
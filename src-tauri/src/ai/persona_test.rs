@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::persona::{persona_infos, select_persona};
+    use crate::ai::{ChatProvider, OllamaClient};
+    use crate::config::{AiConfig, MoodProfile, Persona};
+
+    fn persona(system_prompt: &str, model_name: Option<&str>) -> Persona {
+        Persona {
+            name: String::new(),
+            system_prompt: system_prompt.to_string(),
+            model_name: model_name.map(str::to_string),
+            mood_profiles: Default::default(),
+            sprite_path: None,
+        }
+    }
+
+    fn config_with_personas() -> AiConfig {
+        let mut config = AiConfig {
+            system_prompt: "You are Ethereal.".to_string(),
+            model_name: "llama3.2".to_string(),
+            ..AiConfig::default()
+        };
+        let mut reviewer = persona("You review code tersely.", Some("qwen2.5-coder:7b"));
+        reviewer.name = "Code Reviewer".to_string();
+        reviewer.mood_profiles.insert(
+            "happy".to_string(),
+            MoodProfile {
+                prompt_modifier: "Approve with one word.".to_string(),
+                temperature: Some(0.1),
+                top_p: None,
+                max_tokens: None,
+            },
+        );
+        reviewer.sprite_path = Some("/sprites/owl".to_string());
+        config.personas.insert("reviewer".to_string(), reviewer);
+        config
+            .personas
+            .insert("companion".to_string(), persona("You are cheerful.", None));
+        config
+    }
+
+    #[test]
+    fn test_base_settings_without_active_persona() {
+        let client = OllamaClient::new(config_with_personas());
+        assert_eq!(client.config().active_model(), "llama3.2");
+        assert!(client
+            .build_system_prompt(Some("Happy"))
+            .starts_with("You are Ethereal."));
+    }
+
+    #[test]
+    fn test_active_persona_overrides_prompt_model_and_moods() {
+        let mut config = config_with_personas();
+        select_persona(&mut config, Some("reviewer".to_string())).unwrap();
+        let client = OllamaClient::new(config);
+
+        assert_eq!(client.config().active_model(), "qwen2.5-coder:7b");
+        assert_eq!(
+            client.build_system_prompt(Some("Happy")),
+            "You review code tersely.\n\nIMPORTANT: Approve with one word."
+        );
+        assert_eq!(client.sampling(Some("Happy")).temperature, Some(0.1));
+        // Moods the persona leaves alone come from the shared table.
+        assert!(client
+            .build_system_prompt(Some("Angry"))
+            .contains("irritable and short-tempered"));
+    }
+
+    #[test]
+    fn test_persona_without_model_uses_base_model() {
+        let mut config = config_with_personas();
+        select_persona(&mut config, Some("companion".to_string())).unwrap();
+        assert_eq!(config.active_model(), "llama3.2");
+        assert_eq!(config.active_system_prompt(), "You are cheerful.");
+    }
+
+    #[test]
+    fn test_select_unknown_persona_fails() {
+        let mut config = config_with_personas();
+        assert!(select_persona(&mut config, Some("pirate".to_string())).is_err());
+        assert_eq!(config.active_persona, None);
+
+        select_persona(&mut config, Some("reviewer".to_string())).unwrap();
+        select_persona(&mut config, None).unwrap();
+        assert!(config.persona().is_none());
+    }
+
+    #[test]
+    fn test_persona_infos() {
+        let mut config = config_with_personas();
+        select_persona(&mut config, Some("reviewer".to_string())).unwrap();
+
+        let infos = persona_infos(&config);
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].id, "companion");
+        assert_eq!(infos[0].name, "companion");
+        assert_eq!(infos[0].model, "llama3.2");
+        assert!(!infos[0].active);
+        assert_eq!(infos[1].name, "Code Reviewer");
+        assert_eq!(infos[1].model, "qwen2.5-coder:7b");
+        assert_eq!(infos[1].sprite_path.as_deref(), Some("/sprites/owl"));
+        assert!(infos[1].active);
+    }
+
+    #[test]
+    fn test_set_active_model_targets_persona_override() {
+        let mut config = config_with_personas();
+        select_persona(&mut config, Some("reviewer".to_string())).unwrap();
+        config.set_active_model("qwen2.5-coder:14b".to_string());
+        assert_eq!(config.active_model(), "qwen2.5-coder:14b");
+        assert_eq!(config.model_name, "llama3.2");
+
+        select_persona(&mut config, Some("companion".to_string())).unwrap();
+        config.set_active_model("mistral".to_string());
+        assert_eq!(config.model_name, "mistral");
+    }
+}
@@ -0,0 +1,81 @@
+use crate::ai::{cancel::CancelRegistry, AiError, OllamaClient};
+use crate::config::AppConfig;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+#[cfg(test)]
+#[path = "pull_test.rs"]
+mod pull_test;
+
+/// Payload of the `model-pull-progress` event.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PullProgress {
+    pub model: String,
+    /// Ollama's phase text, e.g. "pulling manifest" or "success".
+    pub status: String,
+    pub digest: Option<String>,
+    /// Bytes downloaded of the current layer.
+    pub completed: Option<u64>,
+    pub total: Option<u64>,
+}
+
+/// Registry key of a running pull.
+pub fn pull_key(model: &str) -> String {
+    format!("pull:{}", model)
+}
+
+/// Download `model` (default: the configured one) into Ollama, emitting
+/// `model-pull-progress` along the way. On success the model setting in
+/// effect (the active persona's or `ai.model_name`) is set to the pulled
+/// model and the saved config is broadcast.
+#[tauri::command]
+pub async fn ai_pull_model(
+    app: AppHandle,
+    jobs: State<'_, CancelRegistry>,
+    model: Option<String>,
+) -> Result<String, AiError> {
+    let config = AppConfig::load(&app).unwrap_or_default();
+    if config.ai.provider == "openai" {
+        return Err(AiError::other(
+            "Pulling models is only supported for Ollama",
+        ));
+    }
+    let model = model
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| config.ai.active_model().to_string());
+
+    let job = jobs.start(&pull_key(&model))?;
+    let client = OllamaClient::new(config.ai.clone());
+    let result = client
+        .pull_model(&model, job.token(), &mut |progress| {
+            if let Err(e) = app.emit("model-pull-progress", &progress) {
+                tracing::error!("Failed to emit model-pull-progress: {}", e);
+            }
+        })
+        .await;
+    drop(job);
+
+    if let Err(e) = result {
+        tracing::warn!("Model pull failed: {}", e);
+        return Err(e);
+    }
+    tracing::info!("Pulled model {}", model);
+
+    // The pull can take hours; start from the settings as they are now, not
+    // as they were when it began, and never save defaults over a config
+    // that failed to load.
+    let mut config = AppConfig::load(&app)?;
+    config.ai.set_active_model(model.clone());
+    config.save(&app)?;
+    if let Err(e) = app.emit("config-updated", config.masked()) {
+        tracing::error!("Failed to emit config-updated: {}", e);
+    }
+    Ok(model)
+}
+
+/// Cancel a running `ai_pull_model`. Returns whether one was running.
+#[tauri::command]
+pub fn ai_cancel_pull(jobs: State<'_, CancelRegistry>, model: String) -> bool {
+    jobs.cancel(&pull_key(&model))
+}
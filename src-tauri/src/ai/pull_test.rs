@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::cancel::CancelRegistry;
+    use crate::ai::pull::pull_key;
+    use crate::ai::stub::{serve, StubResponse};
+    use crate::ai::{AiError, OllamaClient};
+    use crate::config::AiConfig;
+    use tokio::sync::Notify;
+
+    const RECORDED_PULL: &str = concat!(
+        r#"{"status":"pulling manifest"}"#,
+        "\n",
+        r#"{"status":"pulling dde5aa3fc5ff","digest":"sha256:dde5aa3fc5ff","total":2019377376,"completed":1048576}"#,
+        "\n",
+        r#"{"status":"pulling dde5aa3fc5ff","digest":"sha256:dde5aa3fc5ff","total":2019377376,"completed":2019377376}"#,
+        "\n",
+        r#"{"status":"verifying sha256 digest"}"#,
+        "\n",
+        r#"{"status":"writing manifest"}"#,
+        "\n",
+        r#"{"status":"success"}"#,
+        "\n",
+    );
+
+    fn client_for(url: &str) -> OllamaClient {
+        OllamaClient::new(AiConfig {
+            api_endpoint: url.to_string(),
+            ..AiConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_pull_reports_progress_until_success() {
+        let server = serve(vec![StubResponse::ndjson(RECORDED_PULL)]);
+        let client = client_for(&server.url);
+
+        let mut progress = Vec::new();
+        client
+            .pull_model("llama3.2", &Notify::new(), &mut |p| progress.push(p))
+            .await
+            .unwrap();
+
+        assert_eq!(progress.len(), 6);
+        assert_eq!(progress[1].model, "llama3.2");
+        assert_eq!(progress[1].completed, Some(1048576));
+        assert_eq!(progress[1].total, Some(2019377376));
+        assert_eq!(progress.last().unwrap().status, "success");
+
+        let requests = server.requests.lock().unwrap();
+        assert_eq!(requests[0].0, "/api/pull");
+        assert!(requests[0].1.contains("\"model\":\"llama3.2\""));
+    }
+
+    #[tokio::test]
+    async fn test_pull_surfaces_error_records() {
+        let server = serve(vec![StubResponse::ndjson(concat!(
+            r#"{"status":"pulling manifest"}"#,
+            "\n",
+            r#"{"error":"pull model manifest: file does not exist"}"#,
+            "\n",
+        ))]);
+        let client = client_for(&server.url);
+
+        let err = client
+            .pull_model("no-such-model", &Notify::new(), &mut |_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AiError::ModelMissing { model } if model == "no-such-model"));
+    }
+
+    #[tokio::test]
+    async fn test_pull_without_success_is_an_error() {
+        let server = serve(vec![StubResponse::ndjson(concat!(
+            r#"{"status":"pulling manifest"}"#,
+            "\n",
+        ))]);
+        let client = client_for(&server.url);
+
+        assert!(client
+            .pull_model("llama3.2", &Notify::new(), &mut |_| {})
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_pull_stops() {
+        let server = serve(vec![StubResponse::ndjson(RECORDED_PULL)]);
+        let client = client_for(&server.url);
+        let jobs = CancelRegistry::new();
+        let job = jobs.start(&pull_key("llama3.2")).unwrap();
+        assert!(jobs.cancel(&pull_key("llama3.2")));
+
+        let mut progress = Vec::new();
+        let err = client
+            .pull_model("llama3.2", job.token(), &mut |p| progress.push(p))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AiError::Cancelled));
+        assert!(progress.is_empty());
+    }
+}
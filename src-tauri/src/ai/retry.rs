@@ -0,0 +1,58 @@
+use crate::ai::AiResult;
+use crate::config::AiConfig;
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(test)]
+#[path = "retry_test.rs"]
+mod retry_test;
+
+/// Upper bound for a single backoff delay.
+const MAX_DELAY: Duration = Duration::from_secs(8);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &AiConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            base_delay: Duration::from_millis(config.retry_base_delay_ms),
+        }
+    }
+
+    /// Backoff before retry number `attempt` (0-based): `base * 2^attempt`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_DELAY)
+    }
+}
+
+/// Run `op` until it succeeds, fails with a non-retryable error, or the
+/// policy's retries are used up.
+pub async fn with_retry<T, F, Fut>(policy: &RetryPolicy, mut op: F) -> AiResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AiResult<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Err(e) if e.is_retryable() && attempt < policy.max_retries => {
+                let delay = policy.delay(attempt);
+                tracing::warn!(
+                    "AI request failed ({}), retrying in {}ms",
+                    e,
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::retry::RetryPolicy;
+    use crate::ai::stub::{serve, StubResponse};
+    use crate::ai::{AiError, ChatMessage, ChatProvider, OllamaClient, OpenAiClient};
+    use crate::config::AiConfig;
+    use std::time::Duration;
+
+    const OK_REPLY: &str = r#"{"message":{"role":"assistant","content":"Awake now."},"done":true}"#;
+
+    fn config_for(url: &str, max_retries: u32) -> AiConfig {
+        AiConfig {
+            api_endpoint: url.to_string(),
+            max_retries,
+            retry_base_delay_ms: 1,
+            enable_tools: false,
+            ..AiConfig::default()
+        }
+    }
+
+    fn hi() -> Vec<ChatMessage> {
+        vec![ChatMessage {
+            role: "user".to_string(),
+            content: "hi".to_string(),
+        }]
+    }
+
+    /// `failures` warm-up errors followed by a successful reply.
+    fn flaky_server(failures: usize) -> crate::ai::stub::StubServer {
+        let mut responses = vec![StubResponse::status(503, "loading model"); failures];
+        responses.push(StubResponse::json(OK_REPLY));
+        serve(responses)
+    }
+
+    #[test]
+    fn test_delay_doubles_and_is_capped() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_millis(500),
+        };
+        assert_eq!(policy.delay(0), Duration::from_millis(500));
+        assert_eq!(policy.delay(1), Duration::from_millis(1000));
+        assert_eq!(policy.delay(3), Duration::from_millis(4000));
+        assert_eq!(policy.delay(10), Duration::from_secs(8));
+        assert_eq!(policy.delay(40), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        assert!(AiError::from_status(503, "loading", "m").is_retryable());
+        assert!(AiError::from_status(429, "slow down", "m").is_retryable());
+        assert!(AiError::ConnectionRefused {
+            endpoint: "http://localhost:11434".to_string()
+        }
+        .is_retryable());
+        assert!(!AiError::from_status(400, "bad request", "m").is_retryable());
+        assert!(!AiError::Timeout.is_retryable());
+        assert!(!AiError::ModelMissing {
+            model: "m".to_string()
+        }
+        .is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_recovers_after_transient_failures() {
+        let server = flaky_server(2);
+        let client = OllamaClient::new(config_for(&server.url, 2));
+
+        let reply = client.chat(hi(), None).await.unwrap();
+        assert_eq!(reply, "Awake now.");
+        assert_eq!(server.requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let server = flaky_server(3);
+        let client = OllamaClient::new(config_for(&server.url, 2));
+
+        let result = client.chat(hi(), None).await;
+        assert!(matches!(result, Err(AiError::Http { status: 503, .. })));
+        assert_eq!(server.requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_client_errors() {
+        let server = serve(vec![
+            StubResponse::status(400, "bad request"),
+            StubResponse::json(OK_REPLY),
+        ]);
+        let client = OllamaClient::new(config_for(&server.url, 2));
+
+        assert!(client.chat(hi(), None).await.is_err());
+        assert_eq!(server.requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_request_is_retried_before_first_token() {
+        let server = serve(vec![
+            StubResponse::status(502, "upstream starting"),
+            StubResponse {
+                status: 200,
+                content_type: "text/event-stream",
+                body: concat!(
+                    r#"data: {"choices":[{"delta":{"content":"Back"}}]}"#,
+                    "\n\n",
+                    "data: [DONE]\n\n",
+                )
+                .to_string(),
+            },
+        ]);
+        let client = OpenAiClient::new(config_for(&server.url, 1));
+
+        let mut tokens = Vec::new();
+        let reply = client
+            .chat_stream(hi(), None, &mut |t| tokens.push(t.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(reply, "Back");
+        assert_eq!(tokens, vec!["Back"]);
+        assert_eq!(server.requests.lock().unwrap().len(), 2);
+    }
+}
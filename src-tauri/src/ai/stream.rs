@@ -0,0 +1,118 @@
+use crate::ai::{AiError, AiResult};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(test)]
+#[path = "stream_test.rs"]
+mod stream_test;
+
+/// Longest a streamed reply may go without sending anything.
+pub const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Await one step of a streamed response, failing with
+/// [`AiError::Timeout`] when the server stays silent for `limit`.
+pub async fn idle_timeout<T, E>(
+    limit: Duration,
+    step: impl Future<Output = Result<T, E>>,
+) -> AiResult<T>
+where
+    AiError: From<E>,
+{
+    match tokio::time::timeout(limit, step).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(AiError::Timeout),
+    }
+}
+
+/// Splits a chunked HTTP body into lines.
+///
+/// HTTP chunks do not line up with records, so bytes are buffered until a
+/// full line is available.
+#[derive(Default)]
+pub struct LineBuffer {
+    buffer: Vec<u8>,
+}
+
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of bytes and return every non-empty line completed by it.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut lines = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            if let Some(line) = Self::decode(&line) {
+                lines.push(line);
+            }
+        }
+        lines
+    }
+
+    /// Return whatever is left once the body has ended without a trailing newline.
+    pub fn finish(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.buffer);
+        Self::decode(&line)
+    }
+
+    fn decode(line: &[u8]) -> Option<String> {
+        let trimmed = line.trim_ascii();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(String::from_utf8_lossy(trimmed).into_owned())
+        }
+    }
+}
+
+/// Incremental decoder for newline-delimited JSON bodies (Ollama streaming).
+#[derive(Default)]
+pub struct NdjsonDecoder {
+    lines: LineBuffer,
+}
+
+impl NdjsonDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of bytes and return every record completed by it.
+    pub fn push<T: DeserializeOwned>(&mut self, chunk: &[u8]) -> serde_json::Result<Vec<T>> {
+        self.lines
+            .push(chunk)
+            .iter()
+            .map(|line| serde_json::from_str(line))
+            .collect()
+    }
+
+    /// Parse whatever is left once the body has ended without a trailing newline.
+    pub fn finish<T: DeserializeOwned>(&mut self) -> serde_json::Result<Option<T>> {
+        match self.lines.finish() {
+            Some(line) => Ok(Some(serde_json::from_str(&line)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A decoded server-sent event line (OpenAI-compatible streaming).
+#[derive(Debug, PartialEq)]
+pub enum SseEvent {
+    Data(String),
+    Done,
+}
+
+/// Interpret one SSE line. Comments, `event:` and `id:` fields are ignored.
+pub fn parse_sse_line(line: &str) -> Option<SseEvent> {
+    let data = line.strip_prefix("data:")?.trim();
+    if data == "[DONE]" {
+        Some(SseEvent::Done)
+    } else if data.is_empty() {
+        None
+    } else {
+        Some(SseEvent::Data(data.to_string()))
+    }
+}
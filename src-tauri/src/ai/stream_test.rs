@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::stream::{idle_timeout, parse_sse_line, NdjsonDecoder, SseEvent};
+    use crate::ai::AiError;
+    use serde::Deserialize;
+    use std::time::Duration;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Record {
+        n: u32,
+    }
+
+    #[test]
+    fn test_decode_complete_lines() {
+        let mut decoder = NdjsonDecoder::new();
+        let records: Vec<Record> = decoder.push(b"{\"n\":1}\n{\"n\":2}\n").unwrap();
+        assert_eq!(records, vec![Record { n: 1 }, Record { n: 2 }]);
+    }
+
+    #[test]
+    fn test_decode_split_across_chunks() {
+        let mut decoder = NdjsonDecoder::new();
+        let first: Vec<Record> = decoder.push(b"{\"n\":").unwrap();
+        assert!(first.is_empty());
+
+        let second: Vec<Record> = decoder.push(b"7}\n{\"n\"").unwrap();
+        assert_eq!(second, vec![Record { n: 7 }]);
+
+        let third: Vec<Record> = decoder.push(b":8}").unwrap();
+        assert!(third.is_empty());
+        assert_eq!(decoder.finish::<Record>().unwrap(), Some(Record { n: 8 }));
+    }
+
+    #[test]
+    fn test_decode_skips_blank_lines() {
+        let mut decoder = NdjsonDecoder::new();
+        let records: Vec<Record> = decoder.push(b"\n\r\n{\"n\":3}\r\n\n").unwrap();
+        assert_eq!(records, vec![Record { n: 3 }]);
+        assert_eq!(decoder.finish::<Record>().unwrap(), None);
+    }
+
+    #[test]
+    fn test_decode_invalid_line_errors() {
+        let mut decoder = NdjsonDecoder::new();
+        assert!(decoder.push::<Record>(b"not json\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_sse_lines() {
+        assert_eq!(
+            parse_sse_line("data: {\"a\":1}"),
+            Some(SseEvent::Data("{\"a\":1}".to_string()))
+        );
+        assert_eq!(parse_sse_line("data: [DONE]"), Some(SseEvent::Done));
+        assert_eq!(parse_sse_line(": keep-alive"), None);
+        assert_eq!(parse_sse_line("event: message"), None);
+        assert_eq!(parse_sse_line("data:"), None);
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_only_fires_on_silence() {
+        let ready = async { Ok::<_, AiError>(7) };
+        assert_eq!(
+            idle_timeout(Duration::from_millis(50), ready)
+                .await
+                .unwrap(),
+            7
+        );
+
+        let silent = std::future::pending::<Result<(), AiError>>();
+        let result = idle_timeout(Duration::from_millis(10), silent).await;
+        assert!(matches!(result, Err(AiError::Timeout)));
+    }
+}
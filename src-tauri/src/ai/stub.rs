@@ -0,0 +1,118 @@
+//! Minimal HTTP server for exercising the AI clients in tests.
+//!
+//! Each accepted connection is answered with the next canned response and
+//! then closed, so reqwest never reuses a socket between requests.
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone)]
+pub struct StubResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl StubResponse {
+    pub fn json(body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: body.into(),
+        }
+    }
+
+    pub fn ndjson(body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/x-ndjson",
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain",
+            body: body.into(),
+        }
+    }
+}
+
+pub struct StubServer {
+    pub url: String,
+    /// Request lines and bodies received so far, as `(path, body)`.
+    pub requests: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+/// Serve `responses` in order, one per connection, on a random local port.
+pub fn serve(responses: Vec<StubResponse>) -> StubServer {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind stub server");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+
+    std::thread::spawn(move || {
+        for response in responses {
+            let Ok((mut stream, _)) = listener.accept() else {
+                return;
+            };
+            let request = read_request(&mut stream);
+            recorded.lock().unwrap().push(request);
+
+            let head = format!(
+                "HTTP/1.1 {} Stub\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                response.status,
+                response.content_type,
+                response.body.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(response.body.as_bytes());
+            let _ = stream.flush();
+        }
+    });
+
+    StubServer { url, requests }
+}
+
+fn read_request(stream: &mut std::net::TcpStream) -> (String, String) {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+
+    let header_end = loop {
+        let n = stream.read(&mut buf).unwrap_or(0);
+        if n == 0 {
+            return (String::new(), String::new());
+        }
+        data.extend_from_slice(&buf[..n]);
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let path = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or_default()
+        .to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while data.len() < header_end + content_length {
+        let n = stream.read(&mut buf).unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+
+    let body = String::from_utf8_lossy(&data[header_end..]).to_string();
+    (path, body)
+}
@@ -0,0 +1,181 @@
+use thiserror::Error;
+
+#[cfg(test)]
+#[path = "template_test.rs"]
+mod template_test;
+
+/// Variables a user message template may reference.
+pub const VARIABLES: &[&str] = &[
+    "message",
+    "context",
+    "learned",
+    "mood",
+    "state",
+    "cpu",
+    "top_apps",
+    "time",
+    "active_category",
+];
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TemplateError {
+    #[error("unknown variable {{{name}}} (known: {})", VARIABLES.join(", "))]
+    UnknownVariable { name: String },
+    #[error("unclosed '{{' at byte {position}")]
+    Unclosed { position: usize },
+    #[error("unmatched '}}' at byte {position}; write '}}}}' for a literal brace")]
+    Unmatched { position: usize },
+    #[error("template must include {{message}}")]
+    MissingMessage,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Var(&'static str),
+}
+
+/// A parsed user message template.
+///
+/// `{name}` expands to a variable, `{{` and `}}` to literal braces. Lines
+/// whose variables all expand to nothing are left out, so a template can
+/// label optional context without leaving empty labels behind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    lines: Vec<Vec<Segment>>,
+}
+
+impl PromptTemplate {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for line in source.split('\n') {
+            lines.push(parse_line(line, offset)?);
+            offset += line.len() + 1;
+        }
+
+        let template = Self { lines };
+        if !template.variables().any(|name| name == "message") {
+            return Err(TemplateError::MissingMessage);
+        }
+        Ok(template)
+    }
+
+    fn variables(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.lines
+            .iter()
+            .flatten()
+            .filter_map(|segment| match segment {
+                Segment::Var(name) => Some(*name),
+                Segment::Text(_) => None,
+            })
+    }
+
+    pub fn render(&self, vars: &PromptVars) -> String {
+        let mut out = Vec::with_capacity(self.lines.len());
+        for line in &self.lines {
+            let mut has_vars = false;
+            let mut any_value = false;
+            let mut text = String::new();
+            for segment in line {
+                match segment {
+                    Segment::Text(t) => text.push_str(t),
+                    Segment::Var(name) => {
+                        let value = vars.get(name);
+                        has_vars = true;
+                        any_value |= !value.is_empty();
+                        text.push_str(value);
+                    }
+                }
+            }
+            if !has_vars || any_value {
+                out.push(text);
+            }
+        }
+        out.join("\n").trim().to_string()
+    }
+}
+
+fn parse_line(line: &str, offset: usize) -> Result<Vec<Segment>, TemplateError> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let rest = &line[i + 1..];
+                let end = rest.find('}').ok_or(TemplateError::Unclosed {
+                    position: offset + i,
+                })?;
+                let name = rest[..end].trim();
+                let known = VARIABLES.iter().find(|v| **v == name).ok_or_else(|| {
+                    TemplateError::UnknownVariable {
+                        name: name.to_string(),
+                    }
+                })?;
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Var(known));
+                // Skip past the closing brace.
+                while chars.next_if(|&(j, _)| j <= i + 1 + end).is_some() {}
+            }
+            '}' => {
+                return Err(TemplateError::Unmatched {
+                    position: offset + i,
+                })
+            }
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+/// Values for the template variables; empty when unknown.
+#[derive(Debug, Clone, Default)]
+pub struct PromptVars {
+    /// What the user typed.
+    pub message: String,
+    /// Free-form context sent by the frontend.
+    pub context: String,
+    /// Learned preferences (top apps, chat habits).
+    pub learned: String,
+    pub mood: String,
+    pub state: String,
+    /// CPU load, e.g. "42%".
+    pub cpu: String,
+    /// Most used apps, comma separated.
+    pub top_apps: String,
+    /// Local time, e.g. "14:05".
+    pub time: String,
+    pub active_category: String,
+}
+
+impl PromptVars {
+    fn get(&self, name: &str) -> &str {
+        match name {
+            "message" => &self.message,
+            "context" => &self.context,
+            "learned" => &self.learned,
+            "mood" => &self.mood,
+            "state" => &self.state,
+            "cpu" => &self.cpu,
+            "top_apps" => &self.top_apps,
+            "time" => &self.time,
+            "active_category" => &self.active_category,
+            _ => "",
+        }
+    }
+}
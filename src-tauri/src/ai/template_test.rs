@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::template::{PromptTemplate, PromptVars, TemplateError};
+    use crate::config::AiConfig;
+
+    fn vars() -> PromptVars {
+        PromptVars {
+            message: "hi".to_string(),
+            mood: "Happy".to_string(),
+            cpu: "12%".to_string(),
+            time: "09:30".to_string(),
+            ..PromptVars::default()
+        }
+    }
+
+    #[test]
+    fn test_render_substitutes_variables() {
+        let template = PromptTemplate::parse("[{time}] {mood}, CPU {cpu}: {message}").unwrap();
+        assert_eq!(template.render(&vars()), "[09:30] Happy, CPU 12%: hi");
+    }
+
+    #[test]
+    fn test_lines_with_only_empty_variables_are_dropped() {
+        let template = PromptTemplate::parse(
+            "System Context: {context}\nMood: {mood}\nTop apps: {top_apps}\n\nUser Message: {message}",
+        )
+        .unwrap();
+        assert_eq!(template.render(&vars()), "Mood: Happy\n\nUser Message: hi");
+    }
+
+    #[test]
+    fn test_default_template_without_context() {
+        let template = PromptTemplate::parse(&AiConfig::default().user_template).unwrap();
+        assert_eq!(template.render(&vars()), "User Message: hi");
+
+        let with_context = PromptVars {
+            context: "CPU hot".to_string(),
+            ..vars()
+        };
+        assert_eq!(
+            template.render(&with_context),
+            "System Context: CPU hot\n\nUser Message: hi"
+        );
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        let template = PromptTemplate::parse("{{\"mood\": \"{mood}\"}} {message}").unwrap();
+        assert_eq!(template.render(&vars()), "{\"mood\": \"Happy\"} hi");
+    }
+
+    #[test]
+    fn test_unknown_variable_is_an_error() {
+        let err = PromptTemplate::parse("{message} {weather}").unwrap_err();
+        assert_eq!(
+            err,
+            TemplateError::UnknownVariable {
+                name: "weather".to_string()
+            }
+        );
+        assert!(err.to_string().contains("{weather}"));
+    }
+
+    #[test]
+    fn test_malformed_templates() {
+        assert_eq!(
+            PromptTemplate::parse("{message}\n{mood").unwrap_err(),
+            TemplateError::Unclosed { position: 10 }
+        );
+        assert_eq!(
+            PromptTemplate::parse("{message} }").unwrap_err(),
+            TemplateError::Unmatched { position: 10 }
+        );
+        assert_eq!(
+            PromptTemplate::parse("Mood: {mood}").unwrap_err(),
+            TemplateError::MissingMessage
+        );
+    }
+}
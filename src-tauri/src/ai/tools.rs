@@ -0,0 +1,198 @@
+use crate::ai::redact::redact;
+use crate::config::LearningConfig;
+use crate::monitors::GpuStats;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+#[cfg(test)]
+#[path = "tools_test.rs"]
+mod tools_test;
+
+/// Upper bound on model/tool round trips for a single reply.
+pub const MAX_TOOL_ROUNDS: usize = 4;
+
+const DEFAULT_TOP_APPS: usize = 5;
+const MAX_TOP_APPS: usize = 20;
+
+/// A function the model may call, in the `{"type": "function", ...}` shape
+/// shared by Ollama and OpenAI.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolDefinition {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    function: FunctionSpec,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FunctionSpec {
+    name: &'static str,
+    description: &'static str,
+    /// JSON Schema of the arguments object.
+    parameters: Value,
+}
+
+impl ToolDefinition {
+    pub fn function(name: &'static str, description: &'static str, parameters: Value) -> Self {
+        Self {
+            kind: "function",
+            function: FunctionSpec {
+                name,
+                description,
+                parameters,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.function.name
+    }
+}
+
+/// A tool invocation requested by the model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    /// Set by OpenAI-compatible servers; results must echo it back.
+    pub id: Option<String>,
+    pub name: String,
+    pub arguments: Value,
+}
+
+impl ToolCall {
+    /// Parse one entry of a response's `tool_calls` array. Ollama sends
+    /// `arguments` as an object, OpenAI as a JSON-encoded string.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let function = value.get("function")?;
+        let name = function.get("name")?.as_str()?.to_string();
+        let arguments = match function.get("arguments") {
+            Some(Value::String(raw)) if raw.trim().is_empty() => json!({}),
+            Some(Value::String(raw)) => serde_json::from_str(raw).unwrap_or_else(|e| {
+                tracing::warn!("Unparseable arguments for tool {}: {}", name, e);
+                json!({})
+            }),
+            Some(Value::Null) | None => json!({}),
+            Some(other) => other.clone(),
+        };
+        Some(Self {
+            id: value.get("id").and_then(Value::as_str).map(str::to_string),
+            name,
+            arguments,
+        })
+    }
+}
+
+/// Runs the tools a provider advertises to the model.
+pub trait ToolExecutor: Send + Sync {
+    fn definitions(&self) -> Vec<ToolDefinition>;
+
+    /// Run `name` and return its result as JSON text for the model. Failures
+    /// are reported in the result rather than aborting the reply.
+    fn execute(&self, name: &str, arguments: &Value) -> String;
+}
+
+/// Built-in tools answering from the monitor thread's latest reading and
+/// the learned app usage, captured when the chat request starts.
+pub struct SystemTools {
+    stats: Option<GpuStats>,
+    top_apps: Vec<(String, u64)>,
+}
+
+impl SystemTools {
+    pub fn new(stats: Option<GpuStats>, learning: &LearningConfig) -> Self {
+        let mut top_apps: Vec<(String, u64)> = if learning.enabled {
+            learning
+                .top_apps
+                .iter()
+                .map(|(name, count)| (name.clone(), *count))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        top_apps.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Self { stats, top_apps }
+    }
+
+    fn system_stats(&self) -> Value {
+        match &self.stats {
+            Some(s) => json!({
+                "cpu_utilization_percent": s.utilization,
+                "temperature_celsius": s.temperature,
+                "memory_used_mb": s.memory_used,
+                "memory_total_mb": s.memory_total,
+                "vram_used_mb": s.vram.map(|(used, _)| used),
+                "vram_total_mb": s.vram.map(|(_, total)| total),
+                "battery_level_percent": s.battery_level,
+                "battery_state": s.battery_state,
+                "sprite_state": s.state,
+                "sprite_mood": s.mood,
+            }),
+            None => json!({ "error": "No hardware readings available yet" }),
+        }
+    }
+
+    fn active_app(&self) -> Value {
+        match &self.stats {
+            Some(s) => json!({
+                "category": s.active_category,
+                "window_title": redact(&s.active_window),
+            }),
+            None => json!({ "error": "No active window information available yet" }),
+        }
+    }
+
+    fn top_apps(&self, arguments: &Value) -> Value {
+        let limit = arguments
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map(|n| (n as usize).clamp(1, MAX_TOP_APPS))
+            .unwrap_or(DEFAULT_TOP_APPS);
+        let apps: Vec<Value> = self
+            .top_apps
+            .iter()
+            .take(limit)
+            .map(|(name, count)| json!({ "name": redact(name), "samples": count }))
+            .collect();
+        json!({ "apps": apps })
+    }
+}
+
+impl ToolExecutor for SystemTools {
+    fn definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::function(
+                "get_system_stats",
+                "Current CPU utilization, temperature, memory and battery readings of the \
+                 user's computer, plus your own sprite state and mood.",
+                json!({ "type": "object", "properties": {} }),
+            ),
+            ToolDefinition::function(
+                "get_active_app",
+                "Category (Coding, Gaming, Browsing, Idle, Unknown) and title of the \
+                 application the user is currently focused on.",
+                json!({ "type": "object", "properties": {} }),
+            ),
+            ToolDefinition::function(
+                "get_top_apps",
+                "Applications the user spends the most time in, most used first.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "limit": {
+                            "type": "integer",
+                            "description": "How many apps to return (1-20, default 5)."
+                        }
+                    }
+                }),
+            ),
+        ]
+    }
+
+    fn execute(&self, name: &str, arguments: &Value) -> String {
+        let result = match name {
+            "get_system_stats" => self.system_stats(),
+            "get_active_app" => self.active_app(),
+            "get_top_apps" => self.top_apps(arguments),
+            other => json!({ "error": format!("Unknown tool: {}", other) }),
+        };
+        result.to_string()
+    }
+}
@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+    use crate::ai::tools::{SystemTools, ToolCall, ToolExecutor};
+    use crate::config::LearningConfig;
+    use crate::monitors::GpuStats;
+    use serde_json::{json, Value};
+
+    fn sample_stats() -> GpuStats {
+        GpuStats {
+            temperature: 91.5,
+            utilization: 87.0,
+            cpu_utilization: None,
+            gpu_utilization: None,
+            memory_used: 12_000,
+            memory_total: 16_000,
+            vram: None,
+            network_rx: 0,
+            network_tx: 0,
+            disk_read: 0,
+            disk_write: 0,
+            battery_level: 64.0,
+            battery_state: "Discharging".to_string(),
+            power_draw: None,
+            overheating_sensor: None,
+            active_window: "Hidden (Privacy)".to_string(),
+            active_category: "Gaming".to_string(),
+            state: "Overheating".to_string(),
+            mood: "Angry".to_string(),
+        }
+    }
+
+    fn learning_with_apps() -> LearningConfig {
+        let mut learning = LearningConfig::default();
+        learning.top_apps.insert("code".to_string(), 40);
+        learning.top_apps.insert("firefox".to_string(), 25);
+        learning.top_apps.insert("steam".to_string(), 90);
+        learning
+    }
+
+    fn run(tools: &SystemTools, name: &str, arguments: Value) -> Value {
+        serde_json::from_str(&tools.execute(name, &arguments)).unwrap()
+    }
+
+    #[test]
+    fn test_definitions_use_function_schema() {
+        let tools = SystemTools::new(None, &LearningConfig::default());
+        let definitions = serde_json::to_value(tools.definitions()).unwrap();
+
+        let names: Vec<&str> = definitions
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| {
+                assert_eq!(d["type"], "function");
+                assert_eq!(d["function"]["parameters"]["type"], "object");
+                d["function"]["name"].as_str().unwrap()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec!["get_system_stats", "get_active_app", "get_top_apps"]
+        );
+    }
+
+    #[test]
+    fn test_system_stats_report_latest_reading() {
+        let tools = SystemTools::new(Some(sample_stats()), &LearningConfig::default());
+
+        let stats = run(&tools, "get_system_stats", json!({}));
+        assert_eq!(stats["temperature_celsius"], 91.5);
+        assert_eq!(stats["cpu_utilization_percent"], 87.0);
+        assert_eq!(stats["memory_total_mb"], 16_000);
+        assert!(stats["vram_total_mb"].is_null());
+        assert_eq!(stats["sprite_state"], "Overheating");
+
+        let app = run(&tools, "get_active_app", json!({}));
+        assert_eq!(app["category"], "Gaming");
+    }
+
+    #[test]
+    fn test_missing_reading_and_unknown_tool_are_reported() {
+        let tools = SystemTools::new(None, &LearningConfig::default());
+
+        assert!(run(&tools, "get_system_stats", json!({}))["error"].is_string());
+        assert!(run(&tools, "rm_rf", json!({}))["error"]
+            .as_str()
+            .unwrap()
+            .contains("rm_rf"));
+    }
+
+    #[test]
+    fn test_top_apps_sorted_and_limited() {
+        let tools = SystemTools::new(None, &learning_with_apps());
+
+        let result = run(&tools, "get_top_apps", json!({ "limit": 2 }));
+        let apps = result["apps"].as_array().unwrap();
+        assert_eq!(apps.len(), 2);
+        assert_eq!(apps[0]["name"], "steam");
+        assert_eq!(apps[1]["name"], "code");
+
+        let mut disabled = learning_with_apps();
+        disabled.enabled = false;
+        let tools = SystemTools::new(None, &disabled);
+        assert!(run(&tools, "get_top_apps", json!({}))["apps"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tool_call_parsing_accepts_both_argument_styles() {
+        let ollama = ToolCall::from_value(&json!({
+            "function": { "name": "get_top_apps", "arguments": { "limit": 3 } }
+        }))
+        .unwrap();
+        assert_eq!(ollama.id, None);
+        assert_eq!(ollama.arguments["limit"], 3);
+
+        let openai = ToolCall::from_value(&json!({
+            "id": "call_1",
+            "type": "function",
+            "function": { "name": "get_top_apps", "arguments": "{\"limit\":3}" }
+        }))
+        .unwrap();
+        assert_eq!(openai.id.as_deref(), Some("call_1"));
+        assert_eq!(openai.arguments, ollama.arguments);
+
+        assert!(ToolCall::from_value(&json!({ "type": "function" })).is_none());
+    }
+}
@@ -1,5 +1,7 @@
+use crate::ai::template::PromptTemplate;
 use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 
@@ -7,6 +9,9 @@ use tauri::{AppHandle, Emitter, Manager};
 #[path = "config_test.rs"]
 mod config_test;
 
+/// Sent to the webview in place of a stored `ai.api_key`.
+pub const MASKED_API_KEY: &str = "********";
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ConfigState(pub std::sync::Arc<std::sync::RwLock<AppConfig>>);
 
@@ -27,6 +32,14 @@ pub struct AppConfig {
     pub privacy: PrivacyConfig,
     #[serde(default)]
     pub learning: LearningConfig,
+    #[serde(default)]
+    pub commentary: CommentaryConfig,
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
+    #[serde(default)]
+    pub knowledge: KnowledgeConfig,
+    #[serde(default)]
+    pub actions: ActionsConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -158,6 +171,163 @@ impl Default for LearningConfig {
     }
 }
 
+/// Unprompted remarks on state changes. Quiet during `sleep` hours when
+/// those are enabled.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CommentaryConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Minimum gap between two remarks.
+    #[serde(default = "default_commentary_interval")]
+    pub min_interval_minutes: u64,
+    /// A `Working` stretch at least this long earns a remark when it ends.
+    #[serde(default = "default_long_work")]
+    pub long_work_minutes: u64,
+}
+
+fn default_commentary_interval() -> u64 {
+    20
+}
+
+fn default_long_work() -> u64 {
+    45
+}
+
+impl Default for CommentaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            min_interval_minutes: default_commentary_interval(),
+            long_work_minutes: default_long_work(),
+        }
+    }
+}
+
+/// One-click actions offered for copied text.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ClipboardConfig {
+    /// Offer explain/summarize/translate for copied text instead of
+    /// chatting about it.
+    #[serde(default = "default_true")]
+    pub actions_enabled: bool,
+    /// Language the translate action translates into; text already in it
+    /// isn't offered for translation.
+    #[serde(default = "default_translate_to")]
+    pub translate_to: String,
+    /// Character cap on action results, which run longer than chat replies.
+    #[serde(default = "default_action_length")]
+    pub max_result_length: usize,
+}
+
+fn default_translate_to() -> String {
+    "English".to_string()
+}
+
+fn default_action_length() -> usize {
+    600
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            actions_enabled: default_true(),
+            translate_to: default_translate_to(),
+            max_result_length: default_action_length(),
+        }
+    }
+}
+
+/// Notes the sprite answers from: text files under `directory`, embedded
+/// with an Ollama embedding model and searched for every chat message.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct KnowledgeConfig {
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+    /// Folder of `.md` / `.txt` files, searched recursively.
+    #[serde(default)]
+    pub directory: Option<String>,
+    #[serde(default = "default_embedding_model")]
+    pub embedding_model: String,
+    /// Ollama server for embeddings; defaults to `ai.api_endpoint`.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Snippets added to each prompt.
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+    /// Snippets less similar than this (cosine, 0 to 1) are left out.
+    #[serde(default = "default_min_score")]
+    pub min_score: f32,
+    /// Target snippet length; files are split at paragraph breaks.
+    #[serde(default = "default_chunk_chars")]
+    pub chunk_chars: usize,
+}
+
+fn default_embedding_model() -> String {
+    "nomic-embed-text".to_string()
+}
+
+fn default_top_k() -> usize {
+    3
+}
+
+fn default_min_score() -> f32 {
+    0.4
+}
+
+fn default_chunk_chars() -> usize {
+    800
+}
+
+impl Default for KnowledgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_false(),
+            directory: None,
+            embedding_model: default_embedding_model(),
+            endpoint: None,
+            top_k: default_top_k(),
+            min_score: default_min_score(),
+            chunk_chars: default_chunk_chars(),
+        }
+    }
+}
+
+/// Structured replies: the model answers with JSON actions (see
+/// [`SpriteAction`](crate::ai::actions::SpriteAction)) instead of plain text.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ActionsConfig {
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+    /// Actions the model may take besides `say`, which is always allowed.
+    /// `open_url` is left out by default.
+    #[serde(default = "default_allowed_actions")]
+    pub allowed: Vec<String>,
+    /// How long a mood set by the model lasts.
+    #[serde(default = "default_mood_minutes")]
+    pub mood_minutes: u64,
+}
+
+fn default_allowed_actions() -> Vec<String> {
+    ["emote", "set_mood", "set_reminder"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect()
+}
+
+fn default_mood_minutes() -> u64 {
+    10
+}
+
+impl Default for ActionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_false(),
+            allowed: default_allowed_actions(),
+            mood_minutes: default_mood_minutes(),
+        }
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -172,14 +342,9 @@ impl Default for AppConfig {
                 monitor_source: default_monitor_source(),
                 polling_interval_ms: default_polling_interval(),
                 thresholds: ThresholdsConfig::default(),
+                cpu_sensor_labels: default_cpu_sensor_labels(),
             },
-            ai: AiConfig {
-                model_name: default_model(),
-                api_endpoint: default_api_endpoint(),
-                system_prompt: default_system_prompt(),
-                max_response_length: default_max_length(),
-                cooldown_seconds: default_cooldown(),
-            },
+            ai: AiConfig::default(),
             sound: SoundConfig {
                 enabled: default_sound_enabled(),
                 volume: default_volume(),
@@ -217,6 +382,10 @@ impl Default for AppConfig {
                 share_window_title: default_false(),
             },
             learning: LearningConfig::default(),
+            commentary: CommentaryConfig::default(),
+            clipboard: ClipboardConfig::default(),
+            knowledge: KnowledgeConfig::default(),
+            actions: ActionsConfig::default(),
         }
     }
 }
@@ -240,20 +409,32 @@ pub struct HardwareConfig {
     pub polling_interval_ms: u64,
     #[serde(default)]
     pub thresholds: ThresholdsConfig,
+    /// Temperature sensors that count as the CPU, matched as case-insensitive
+    /// substrings of the sensor label (e.g. "Package id 0", "k10temp Tctl").
+    /// Matching sensors are averaged.
+    #[serde(default = "default_cpu_sensor_labels")]
+    pub cpu_sensor_labels: Vec<String>,
 }
 
+/// Overheat thresholds in °C, one per kind of temperature sensor.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ThresholdsConfig {
     #[serde(default = "default_gpu_temp")]
     pub nvidia_temp: f32,
+    /// AMD and Intel GPUs.
     #[serde(default = "default_gpu_temp")]
     pub amd_temp: f32,
     #[serde(default = "default_cpu_temp")]
     pub cpu_temp: f32,
+    #[serde(default = "default_battery_temp")]
+    pub battery_temp: f32,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AiConfig {
+    /// Wire protocol of the LLM server: "ollama" or "openai" (OpenAI-compatible).
+    #[serde(default = "default_ai_provider")]
+    pub provider: String,
     #[serde(default = "default_model")]
     pub model_name: String,
     #[serde(default = "default_api_endpoint")]
@@ -264,6 +445,121 @@ pub struct AiConfig {
     pub max_response_length: usize,
     #[serde(default = "default_cooldown")]
     pub cooldown_seconds: u64,
+    /// Bearer token for OpenAI-compatible servers that require one. The
+    /// webview only ever sees [`MASKED_API_KEY`] in its place.
+    pub api_key: Option<String>,
+    /// Estimated tokens (prompt plus reply) a request may use before older
+    /// turns are folded into a summary. `0` disables compaction.
+    #[serde(default = "default_context_budget")]
+    pub context_budget_tokens: usize,
+    /// Most recent messages always sent verbatim.
+    #[serde(default = "default_keep_recent")]
+    pub keep_recent_messages: usize,
+    /// Let the model call built-in tools (system stats, active app, top apps)
+    /// for non-streaming replies. Turn off for models without tool support.
+    #[serde(default = "default_true")]
+    pub enable_tools: bool,
+    /// Extra attempts after a retryable failure (server starting, 429/5xx).
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles with every further attempt.
+    #[serde(default = "default_retry_base_delay")]
+    pub retry_base_delay_ms: u64,
+    /// Prompt modifier and sampling per sprite mood, keyed by lowercase mood
+    /// name ("happy", "sleeping", ...). Moods without an entry get the plain
+    /// system prompt and the server's default sampling.
+    #[serde(default = "default_mood_profiles")]
+    pub mood_profiles: BTreeMap<String, MoodProfile>,
+    /// Layout of the user message sent to the model; see
+    /// [`PromptTemplate`](crate::ai::template::PromptTemplate) for the syntax
+    /// and [`VARIABLES`](crate::ai::template::VARIABLES) for the variables.
+    #[serde(default = "default_user_template")]
+    pub user_template: String,
+    /// Named personas, keyed by id.
+    #[serde(default)]
+    pub personas: BTreeMap<String, Persona>,
+    /// Id of the persona in use; `None` uses the settings above as-is.
+    #[serde(default)]
+    pub active_persona: Option<String>,
+    /// Answer from the canned phrase banks (`phrases.toml`) when the AI
+    /// server can't be reached, instead of returning an error.
+    #[serde(default = "default_true")]
+    pub offline_fallback: bool,
+}
+
+impl AiConfig {
+    /// The active persona, if `active_persona` names a configured one.
+    pub fn persona(&self) -> Option<&Persona> {
+        self.active_persona
+            .as_ref()
+            .and_then(|id| self.personas.get(id))
+    }
+
+    pub fn active_system_prompt(&self) -> &str {
+        self.persona()
+            .map(|p| p.system_prompt.as_str())
+            .filter(|prompt| !prompt.trim().is_empty())
+            .unwrap_or(&self.system_prompt)
+    }
+
+    pub fn active_model(&self) -> &str {
+        self.persona()
+            .and_then(|p| p.model_name.as_deref())
+            .filter(|model| !model.is_empty())
+            .unwrap_or(&self.model_name)
+    }
+
+    /// Point whichever model setting is in effect at `model`: the active
+    /// persona's override if it has one, `model_name` otherwise.
+    pub fn set_active_model(&mut self, model: String) {
+        let persona = self
+            .active_persona
+            .as_ref()
+            .and_then(|id| self.personas.get_mut(id))
+            .filter(|p| p.model_name.as_deref().is_some_and(|m| !m.is_empty()));
+        match persona {
+            Some(persona) => persona.model_name = Some(model),
+            None => self.model_name = model,
+        }
+    }
+
+    /// Profile for `mood`, preferring the active persona's entry.
+    pub fn mood_profile(&self, mood: &str) -> Option<&MoodProfile> {
+        let key = mood.to_lowercase();
+        self.persona()
+            .and_then(|p| p.mood_profiles.get(&key))
+            .or_else(|| self.mood_profiles.get(&key))
+    }
+}
+
+/// An alternative personality: its own prompt, and optionally its own model,
+/// mood tuning and sprite.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Persona {
+    /// Shown in the persona picker; the id is used when empty.
+    #[serde(default)]
+    pub name: String,
+    /// Replaces `ai.system_prompt`.
+    #[serde(default)]
+    pub system_prompt: String,
+    /// Replaces `ai.model_name`.
+    pub model_name: Option<String>,
+    /// Entries replace the `ai.mood_profiles` entry of the same mood.
+    #[serde(default)]
+    pub mood_profiles: BTreeMap<String, MoodProfile>,
+    /// Sprite directory; replaces `interaction.custom_sprite_path`.
+    pub sprite_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MoodProfile {
+    /// Appended to the system prompt while in this mood.
+    #[serde(default)]
+    pub prompt_modifier: String,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    /// Replaces the budget derived from `max_response_length`.
+    pub max_tokens: Option<u32>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -295,6 +591,15 @@ fn default_gpu_temp() -> f32 {
 fn default_cpu_temp() -> f32 {
     85.0
 }
+fn default_battery_temp() -> f32 {
+    50.0
+}
+pub(crate) fn default_cpu_sensor_labels() -> Vec<String> {
+    vec!["cpu".to_string(), "package".to_string(), "core".to_string()]
+}
+fn default_ai_provider() -> String {
+    "ollama".to_string()
+}
 fn default_model() -> String {
     "llama3.2".to_string()
 }
@@ -316,6 +621,83 @@ fn default_max_length() -> usize {
 fn default_cooldown() -> u64 {
     30
 }
+fn default_context_budget() -> usize {
+    2048
+}
+fn default_keep_recent() -> usize {
+    6
+}
+fn default_max_retries() -> u32 {
+    2
+}
+fn default_retry_base_delay() -> u64 {
+    500
+}
+fn default_user_template() -> String {
+    "System Context: {context}\nLearned Context: {learned}\n\nUser Message: {message}".to_string()
+}
+fn default_mood_profiles() -> BTreeMap<String, MoodProfile> {
+    let profile = |prompt_modifier: &str, temperature, top_p, max_tokens| MoodProfile {
+        prompt_modifier: prompt_modifier.to_string(),
+        temperature: Some(temperature),
+        top_p: Some(top_p),
+        max_tokens,
+    };
+    BTreeMap::from([
+        (
+            "happy".to_string(),
+            profile("You are feeling cheerful and helpful.", 0.8, 0.9, None),
+        ),
+        (
+            "excited".to_string(),
+            profile(
+                "You are very energetic and enthusiastic! Use exclamation marks.",
+                1.0,
+                0.95,
+                None,
+            ),
+        ),
+        (
+            "tired".to_string(),
+            profile(
+                "You are exhausted. Use short sentences and sound sleepy.",
+                0.5,
+                0.8,
+                Some(40),
+            ),
+        ),
+        (
+            "bored".to_string(),
+            profile("You are uninterested and slightly cynical.", 0.7, 0.9, None),
+        ),
+        (
+            "angry".to_string(),
+            profile("You are irritable and short-tempered.", 0.7, 0.85, Some(40)),
+        ),
+        (
+            "sad".to_string(),
+            profile("You are melancholic and soft-spoken.", 0.6, 0.85, None),
+        ),
+        (
+            "curious".to_string(),
+            profile(
+                "You are inquisitive and eager to learn. Ask follow-up questions.",
+                0.9,
+                0.95,
+                None,
+            ),
+        ),
+        (
+            "sleeping".to_string(),
+            profile(
+                "You are drowsy and barely awake. Use very short, mumbled responses.",
+                0.3,
+                0.7,
+                Some(24),
+            ),
+        ),
+    ])
+}
 fn default_sound_enabled() -> bool {
     true
 }
@@ -329,12 +711,49 @@ impl Default for ThresholdsConfig {
             nvidia_temp: default_gpu_temp(),
             amd_temp: default_gpu_temp(),
             cpu_temp: default_cpu_temp(),
+            battery_temp: default_battery_temp(),
+        }
+    }
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_ai_provider(),
+            model_name: default_model(),
+            api_endpoint: default_api_endpoint(),
+            system_prompt: default_system_prompt(),
+            max_response_length: default_max_length(),
+            cooldown_seconds: default_cooldown(),
+            api_key: None,
+            context_budget_tokens: default_context_budget(),
+            keep_recent_messages: default_keep_recent(),
+            enable_tools: default_true(),
+            max_retries: default_max_retries(),
+            retry_base_delay_ms: default_retry_base_delay(),
+            mood_profiles: default_mood_profiles(),
+            user_template: default_user_template(),
+            personas: BTreeMap::new(),
+            active_persona: None,
+            offline_fallback: true,
         }
     }
 }
 
 impl AppConfig {
+    /// Read the config file, creating it with defaults on first run.
+    ///
+    /// Settings that parse but are invalid (see [`validate`](Self::validate))
+    /// are logged and replaced with their defaults one by one, so a typo in
+    /// one field never costs the rest of the file.
     pub fn load(app: &AppHandle) -> anyhow::Result<Self> {
+        let mut config = Self::read(app)?;
+        config.sanitize();
+        Ok(config)
+    }
+
+    /// Parse the config file as-is, without validating it.
+    fn read(app: &AppHandle) -> anyhow::Result<Self> {
         let config_dir = app.path().app_config_dir()?;
         if !config_dir.exists() {
             std::fs::create_dir_all(&config_dir)?;
@@ -355,6 +774,92 @@ impl AppConfig {
         Ok(config.try_deserialize()?)
     }
 
+    /// Replace each setting [`validate`](Self::validate) would reject with
+    /// its default, logging what was dropped.
+    pub fn sanitize(&mut self) {
+        if let Err(e) = PromptTemplate::parse(&self.ai.user_template) {
+            tracing::error!("Invalid ai.user_template ({}), using the default", e);
+            self.ai.user_template = AiConfig::default().user_template;
+        }
+        self.actions.allowed.retain(|action| {
+            let known = crate::ai::actions::ACTION_TYPES.contains(&action.as_str());
+            if !known {
+                tracing::error!("Ignoring unknown action in actions.allowed: {}", action);
+            }
+            known
+        });
+        if !crate::monitors::factory::MONITOR_SOURCES
+            .contains(&self.hardware.monitor_source.as_str())
+        {
+            tracing::error!(
+                "Unknown hardware.monitor_source ({}), using auto",
+                self.hardware.monitor_source
+            );
+            self.hardware.monitor_source = default_monitor_source();
+        }
+        let labels = &mut self.hardware.cpu_sensor_labels;
+        for label in labels.iter_mut() {
+            *label = label.trim().to_string();
+        }
+        if labels.iter().any(|label| label.is_empty()) {
+            tracing::error!("Ignoring blank labels in hardware.cpu_sensor_labels");
+            labels.retain(|label| !label.is_empty());
+        }
+        if labels.is_empty() {
+            tracing::error!("Empty hardware.cpu_sensor_labels, using the defaults");
+            self.hardware.cpu_sensor_labels = default_cpu_sensor_labels();
+        }
+    }
+
+    /// Check settings serde cannot, such as prompt templates.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        PromptTemplate::parse(&self.ai.user_template)
+            .map_err(|e| anyhow::anyhow!("Invalid ai.user_template: {}", e))?;
+        if let Some(unknown) = self
+            .actions
+            .allowed
+            .iter()
+            .find(|a| !crate::ai::actions::ACTION_TYPES.contains(&a.as_str()))
+        {
+            anyhow::bail!("Unknown action in actions.allowed: {}", unknown);
+        }
+        if !crate::monitors::factory::MONITOR_SOURCES
+            .contains(&self.hardware.monitor_source.as_str())
+        {
+            anyhow::bail!(
+                "Unknown hardware.monitor_source: {}",
+                self.hardware.monitor_source
+            );
+        }
+        let labels = &self.hardware.cpu_sensor_labels;
+        if labels.is_empty() {
+            anyhow::bail!("hardware.cpu_sensor_labels needs at least one label");
+        }
+        // A blank label would match every sensor.
+        if labels.iter().any(|label| label.trim().is_empty()) {
+            anyhow::bail!("hardware.cpu_sensor_labels must not contain blank labels");
+        }
+        Ok(())
+    }
+
+    /// Copy for the webview, with the API key masked.
+    pub fn masked(&self) -> Self {
+        let mut config = self.clone();
+        if config.ai.api_key.as_deref().is_some_and(|k| !k.is_empty()) {
+            config.ai.api_key = Some(MASKED_API_KEY.to_string());
+        }
+        config
+    }
+
+    /// Keep `stored`'s API key when the webview sent back the mask or
+    /// nothing in its place.
+    pub fn keep_api_key(&mut self, stored: &AppConfig) {
+        let sent = self.ai.api_key.as_deref().unwrap_or_default();
+        if sent.is_empty() || sent == MASKED_API_KEY {
+            self.ai.api_key = stored.ai.api_key.clone();
+        }
+    }
+
     pub fn save(&self, app: &AppHandle) -> anyhow::Result<()> {
         let config_path = app.path().app_config_dir()?.join("ethereal.toml");
         let toml = toml::to_string_pretty(self)?;
@@ -365,16 +870,20 @@ impl AppConfig {
 
 #[tauri::command]
 pub fn get_config(app: AppHandle) -> Result<AppConfig, String> {
-    AppConfig::load(&app).map_err(|e| e.to_string())
+    AppConfig::load(&app)
+        .map(|config| config.masked())
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn update_config(app: AppHandle, config: AppConfig) -> Result<(), String> {
+pub fn update_config(app: AppHandle, mut config: AppConfig) -> Result<(), String> {
+    config.validate().map_err(|e| e.to_string())?;
+    config.keep_api_key(&AppConfig::load(&app).map_err(|e| e.to_string())?);
     config.save(&app).map_err(|e| e.to_string())?;
 
     let _ = crate::utils::hotkeys::refresh_hotkeys(&app);
 
-    app.emit("config-updated", config)
+    app.emit("config-updated", config.masked())
         .map_err(|e| e.to_string())?;
     Ok(())
 }
@@ -427,20 +936,29 @@ pub fn watch_config(app: AppHandle) {
 
         for result in rx {
             match result {
-                Ok(_events) => {
-                    if let Ok(new_config) = AppConfig::load(&app_handle) {
+                Ok(_events) => match AppConfig::read(&app_handle)
+                    .and_then(|config| config.validate().map(|_| config))
+                {
+                    Ok(new_config) => {
                         tracing::info!("Config reloaded from disk");
 
                         // Update shared state if it exists
-                        if let Some(state) = app_handle.try_state::<ConfigState>() {
+                        let previous = app_handle.try_state::<ConfigState>().map(|state| {
                             let mut w = state.0.write().unwrap();
-                            *w = new_config.clone();
-                        }
+                            std::mem::replace(&mut *w, new_config.clone())
+                        });
 
                         let _ = crate::utils::hotkeys::refresh_hotkeys(&app_handle);
-                        app_handle.emit("config-updated", new_config).ok();
+                        // Re-indexing re-embeds every note, so skip it unless
+                        // the knowledge settings actually changed.
+                        if previous.is_none_or(|old| old.knowledge != new_config.knowledge) {
+                            crate::ai::knowledge::refresh(&app_handle);
+                        }
+                        app_handle.emit("config-updated", new_config.masked()).ok();
                     }
-                }
+                    // Keep the previous settings until the file is fixed.
+                    Err(e) => tracing::error!("Ignoring invalid config: {}", e),
+                },
                 Err(e) => tracing::error!("Watch error: {:?}", e),
             }
         }
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use crate::config::AppConfig;
+    use crate::config::{default_cpu_sensor_labels, AiConfig, AppConfig, MASKED_API_KEY};
 
     #[test]
     fn test_default_config_values() {
@@ -14,6 +14,7 @@ mod tests {
         assert_eq!(config.hardware.thresholds.nvidia_temp, 80.0);
 
         assert_eq!(config.ai.model_name, "llama3.2");
+        assert_eq!(config.ai.provider, "ollama");
         assert!(config.sound.enabled);
     }
 
@@ -46,8 +47,10 @@ mod tests {
             nvidia_temp = 75.0
 
             [ai]
+            provider = "openai"
             model_name = "test-model"
             api_endpoint = "http://test:1234"
+            api_key = "sk-test"
 
             [sound]
             enabled = false
@@ -91,6 +94,8 @@ mod tests {
         assert_eq!(config.hardware.monitor_source, "nvidia");
         assert_eq!(config.hardware.thresholds.nvidia_temp, 75.0);
         assert_eq!(config.ai.model_name, "test-model");
+        assert_eq!(config.ai.provider, "openai");
+        assert_eq!(config.ai.api_key.as_deref(), Some("sk-test"));
         assert!(!config.sound.enabled);
         assert_eq!(config.sound.volume, 0.8);
         assert_eq!(config.mood.boredom_threshold_cpu, 10.0);
@@ -104,5 +109,133 @@ mod tests {
         assert!(config.privacy.share_window_title);
 
         assert_eq!(config.hardware.thresholds.cpu_temp, 85.0);
+        assert_eq!(config.hardware.thresholds.battery_temp, 50.0);
+        assert_eq!(
+            config.hardware.cpu_sensor_labels,
+            vec!["cpu", "package", "core"]
+        );
+    }
+
+    #[test]
+    fn test_mood_profiles_default_and_override() {
+        let defaults = AppConfig::default();
+        for mood in [
+            "Happy", "Excited", "Tired", "Bored", "Angry", "Sad", "Curious", "Sleeping",
+        ] {
+            assert!(defaults.ai.mood_profile(mood).is_some(), "{}", mood);
+        }
+
+        let config: AiConfig = toml::from_str(
+            r#"
+            [mood_profiles.sleeping]
+            prompt_modifier = "Snore."
+            temperature = 0.1
+            max_tokens = 8
+            "#,
+        )
+        .unwrap();
+
+        let sleeping = config.mood_profile("Sleeping").unwrap();
+        assert_eq!(sleeping.prompt_modifier, "Snore.");
+        assert_eq!(sleeping.temperature, Some(0.1));
+        assert_eq!(sleeping.top_p, None);
+        assert_eq!(sleeping.max_tokens, Some(8));
+        // An explicit table replaces the defaults.
+        assert!(config.mood_profile("Happy").is_none());
+    }
+
+    #[test]
+    fn test_sanitize_resets_only_invalid_fields() {
+        let mut config = AppConfig::default();
+        config.ai.model_name = "mistral".to_string();
+        config.ai.user_template = "{message} {weather}".to_string();
+        config.actions.allowed = vec!["emote".to_string(), "self_destruct".to_string()];
+        config.hardware.monitor_source = "tpu".to_string();
+        config.hardware.cpu_sensor_labels = vec![" ".to_string()];
+        assert!(config.validate().is_err());
+
+        config.sanitize();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.ai.user_template, AiConfig::default().user_template);
+        assert_eq!(config.actions.allowed, vec!["emote"]);
+        assert_eq!(config.hardware.monitor_source, "auto");
+        assert_eq!(
+            config.hardware.cpu_sensor_labels,
+            default_cpu_sensor_labels()
+        );
+        assert_eq!(config.ai.model_name, "mistral");
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_template_variable() {
+        let mut config = AppConfig::default();
+        assert!(config.validate().is_ok());
+
+        config.ai.user_template = "{message} (weather: {weather})".to_string();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("ai.user_template"), "{}", err);
+        assert!(err.contains("{weather}"), "{}", err);
+    }
+
+    #[test]
+    fn test_api_key_is_masked_for_the_webview_and_kept_on_save() {
+        let mut stored = AppConfig::default();
+        stored.ai.api_key = Some("sk-secret".to_string());
+
+        let sent = stored.masked();
+        assert_eq!(sent.ai.api_key.as_deref(), Some(MASKED_API_KEY));
+        assert!(!format!("{:?}", sent).contains("sk-secret"));
+
+        for returned in [Some(MASKED_API_KEY.to_string()), Some(String::new()), None] {
+            let mut saved = sent.clone();
+            saved.ai.api_key = returned;
+            saved.keep_api_key(&stored);
+            assert_eq!(saved.ai.api_key.as_deref(), Some("sk-secret"));
+        }
+
+        let mut replaced = sent.clone();
+        replaced.ai.api_key = Some("sk-new".to_string());
+        replaced.keep_api_key(&stored);
+        assert_eq!(replaced.ai.api_key.as_deref(), Some("sk-new"));
+
+        assert_eq!(AppConfig::default().masked().ai.api_key, None);
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_cpu_sensor_labels() {
+        let mut config = AppConfig::default();
+        config.hardware.cpu_sensor_labels = Vec::new();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("hardware.cpu_sensor_labels"), "{}", err);
+    }
+
+    #[test]
+    fn test_blank_cpu_sensor_labels_are_rejected_and_dropped() {
+        let mut config = AppConfig::default();
+        config.hardware.cpu_sensor_labels = vec![" k10temp ".to_string(), String::new()];
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("blank"), "{}", err);
+        config.sanitize();
+        assert_eq!(config.hardware.cpu_sensor_labels, vec!["k10temp"]);
+        assert!(config.validate().is_ok());
+
+        config.hardware.cpu_sensor_labels = vec!["  ".to_string()];
+        assert!(config.validate().is_err());
+        config.sanitize();
+        assert_eq!(
+            config.hardware.cpu_sensor_labels,
+            default_cpu_sensor_labels()
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_monitor_source() {
+        let mut config = AppConfig::default();
+        config.hardware.monitor_source = "composite".to_string();
+        assert!(config.validate().is_ok());
+
+        config.hardware.monitor_source = "tpu".to_string();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("hardware.monitor_source"), "{}", err);
     }
 }
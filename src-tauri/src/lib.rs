@@ -6,33 +6,43 @@ pub mod config;
 pub mod monitors;
 pub mod utils;
 
-#[tauri::command]
-async fn chat_with_ethereal(
-    app: tauri::AppHandle,
+/// The new message as the model sees it, and as the stored conversation
+/// keeps it: fenced as untrusted (and so redacted) unless the user typed it.
+fn user_text(message: &str, source: Option<&str>) -> String {
+    match source.filter(|s| *s != "user") {
+        Some(source) => crate::ai::redact::fence(source, message),
+        None => message.to_string(),
+    }
+}
+
+/// Wrap the new user message with system and learned context, laid out by
+/// `AiConfig.user_template`.
+///
+/// Context from outside the app is redacted. A message whose `source` is not
+/// the user (e.g. "clipboard") is also fenced as untrusted.
+fn build_user_message(
+    app: &tauri::AppHandle,
+    config: &crate::config::AppConfig,
     message: String,
-    history: Vec<crate::ai::ChatMessage>,
     system_context: Option<String>,
-    mood: Option<String>,
-) -> Result<String, String> {
-    use crate::config::AppConfig;
-
-    let config = AppConfig::load(&app).unwrap_or_default();
+    mood: Option<&str>,
+    source: Option<&str>,
+) -> crate::ai::ChatMessage {
+    use crate::ai::redact::redact;
+    use crate::ai::template::{PromptTemplate, PromptVars};
 
     // Track interaction
     if let Some(learning) = app.try_state::<crate::monitors::learning::LearningMonitor>() {
         learning.track_interaction();
     }
 
-    let client = crate::ai::OllamaClient::new(config.ai);
-
-    let mut full_history = history;
-
     // Inject learned preferences into context
     let mut learning_context = String::new();
+    let mut top_apps = String::new();
     if config.learning.enabled {
-        let top_apps: Vec<_> = config.learning.top_apps.iter().collect();
+        let top_apps_list: Vec<_> = config.learning.top_apps.iter().collect();
         // Sort by usage count descending
-        let mut sorted_apps = top_apps;
+        let mut sorted_apps = top_apps_list;
         sorted_apps.sort_by(|a, b| b.1.cmp(a.1));
 
         let top_3_apps: Vec<String> = sorted_apps
@@ -42,40 +52,494 @@ async fn chat_with_ethereal(
             .collect();
 
         if !top_3_apps.is_empty() {
-            learning_context.push_str(&format!("User's Top Apps: {}. ", top_3_apps.join(", ")));
+            top_apps = redact(&top_3_apps.join(", "));
+            learning_context.push_str(&format!("User's Top Apps: {}. ", top_apps));
         }
         if config.learning.interaction_count > 100 {
             learning_context.push_str("User is a frequent chatter. ");
         }
     }
 
-    let user_content = if let Some(ctx) = system_context {
-        if !learning_context.is_empty() {
-            format!(
-                "System Context: {}\nLearned Context: {}\n\nUser Message: {}",
-                ctx, learning_context, message
+    let stats = app
+        .try_state::<crate::monitors::LatestStats>()
+        .and_then(|latest| latest.get());
+    let vars = PromptVars {
+        message: user_text(&message, source),
+        context: system_context.as_deref().map(redact).unwrap_or_default(),
+        learned: learning_context.trim_end().to_string(),
+        mood: mood
+            .map(str::to_string)
+            .or_else(|| stats.as_ref().map(|s| s.mood.clone()))
+            .unwrap_or_default(),
+        state: stats.as_ref().map(|s| s.state.clone()).unwrap_or_default(),
+        cpu: stats
+            .as_ref()
+            .map(|s| format!("{:.0}%", s.utilization))
+            .unwrap_or_default(),
+        top_apps,
+        time: chrono::Local::now().format("%H:%M").to_string(),
+        active_category: stats
+            .as_ref()
+            .map(|s| redact(&s.active_category))
+            .unwrap_or_default(),
+    };
+
+    // The template was validated when the config was loaded.
+    let template = PromptTemplate::parse(&config.ai.user_template).unwrap_or_else(|e| {
+        tracing::warn!("Invalid ai.user_template ({}), using the default", e);
+        PromptTemplate::parse(&crate::config::AiConfig::default().user_template)
+            .expect("default template is valid")
+    });
+
+    crate::ai::ChatMessage {
+        role: "user".to_string(),
+        content: template.render(&vars),
+    }
+}
+
+/// Prepend the notes most relevant to `query` to `user_message`, when the
+/// knowledge base is enabled. Failures only cost the notes.
+async fn add_notes(
+    app: &tauri::AppHandle,
+    config: &crate::config::AppConfig,
+    mut user_message: crate::ai::ChatMessage,
+    query: &str,
+) -> crate::ai::ChatMessage {
+    use crate::ai::knowledge::{notes_block, KnowledgeBase, OllamaEmbedder};
+
+    if !config.knowledge.enabled {
+        return user_message;
+    }
+    let Some(knowledge) = app.try_state::<KnowledgeBase>() else {
+        return user_message;
+    };
+    let embedder = OllamaEmbedder::from_config(config);
+    match knowledge
+        .retrieve(&embedder, &config.knowledge, query)
+        .await
+    {
+        Ok(snippets) => {
+            if let Some(notes) = notes_block(&snippets) {
+                user_message.content = format!("{}\n\n{}", notes, user_message.content);
+            }
+        }
+        Err(e) => tracing::warn!("Answering without notes: {}", e),
+    }
+    user_message
+}
+
+/// Reject the request if the previous one is still inside
+/// `AiConfig.cooldown_seconds`. The cooldown only starts once the returned
+/// permit is kept, so failed and cancelled requests don't count.
+fn check_cooldown<'a>(
+    app: &'a tauri::AppHandle,
+    config: &crate::config::AppConfig,
+) -> Result<Option<crate::ai::limits::CooldownPermit<'a>>, crate::ai::AiError> {
+    let Some(cooldown) = app.try_state::<crate::ai::limits::ChatCooldown>() else {
+        return Ok(None);
+    };
+    let window = std::time::Duration::from_secs(config.ai.cooldown_seconds);
+    cooldown
+        .inner()
+        .acquire(window, std::time::Instant::now())
+        .map(Some)
+        .map_err(|remaining_secs| crate::ai::AiError::Cooldown { remaining_secs })
+}
+
+/// Build the conversation sent to the model: prior turns (compacted to the
+/// context budget) plus `user_message`. Prior turns are whatever the
+/// frontend sent, or the active stored conversation when it sent nothing.
+///
+/// Also returns the id of the conversation the new exchange should be saved to.
+async fn compose_messages(
+    app: &tauri::AppHandle,
+    config: &crate::config::AppConfig,
+    client: &dyn crate::ai::ChatProvider,
+    user_message: crate::ai::ChatMessage,
+    history: Option<Vec<crate::ai::ChatMessage>>,
+    mood: Option<&str>,
+    instructions: Option<String>,
+) -> (Option<String>, Vec<crate::ai::ChatMessage>) {
+    use crate::ai::context;
+
+    let store = app.try_state::<crate::ai::history::ConversationStore>();
+    let conversation_id = store.as_ref().and_then(|store| match store.active() {
+        Ok(c) => Some(c.id),
+        Err(e) => {
+            tracing::error!("Failed to open conversation: {}", e);
+            None
+        }
+    });
+
+    let from_store = history.is_none();
+    let (summary, history, start) = match (history, &store, &conversation_id) {
+        (Some(history), _, _) => (None, history, 0),
+        (None, Some(store), Some(id)) => store.context(id).unwrap_or_default(),
+        _ => (None, Vec::new(), 0),
+    };
+    // Older stored turns and frontend-supplied ones were never cleaned; treat
+    // them like any other outside context.
+    let summary = summary.map(|s| crate::ai::redact::redact(&s));
+    let history = history
+        .into_iter()
+        .map(|m| crate::ai::ChatMessage {
+            content: crate::ai::redact::redact(&m.content),
+            role: m.role,
+        })
+        .collect();
+
+    let instructions = instructions.map(|content| crate::ai::ChatMessage {
+        role: "system".to_string(),
+        content,
+    });
+    let fixed_tokens = context::estimate_tokens(&client.build_system_prompt(mood))
+        + context::estimate_message_tokens(instructions.as_slice())
+        + context::estimate_message_tokens(std::slice::from_ref(&user_message))
+        + crate::ai::limits::token_limit(config.ai.max_response_length).unwrap_or(0) as usize;
+
+    let compacted = context::compact(client, summary, history, fixed_tokens).await;
+
+    if from_store && compacted.folded > 0 {
+        if let (Some(store), Some(id)) = (&store, &conversation_id) {
+            // Turns were counted from this request's read; another request
+            // may have folded some of them since.
+            let covered = start + compacted.folded;
+            if let Err(e) = store.fold_into_summary(id, compacted.summary.clone(), covered) {
+                tracing::error!("Failed to save conversation summary: {}", e);
+            }
+        }
+    }
+
+    // The provider puts the persona prompt first, so leading instructions
+    // land right after it and ahead of the summary and history.
+    let mut messages: Vec<_> = instructions.into_iter().collect();
+    messages.extend(compacted.messages);
+    messages.push(user_message);
+    (conversation_id, messages)
+}
+
+fn remember_turn(app: &tauri::AppHandle, conversation_id: Option<String>, user: &str, reply: &str) {
+    if let (Some(id), Some(store)) = (
+        conversation_id,
+        app.try_state::<crate::ai::history::ConversationStore>(),
+    ) {
+        if let Err(e) = store.append_turn(&id, user, reply) {
+            tracing::error!("Failed to save conversation: {}", e);
+        }
+    }
+}
+
+/// Answer from the canned phrase banks when the AI server can't be reached
+/// and `AiConfig.offline_fallback` is on; otherwise hand `error` back.
+/// Keyword intents only apply to text the user typed. Callers keep canned
+/// replies out of the stored conversation.
+fn offline_reply(
+    app: &tauri::AppHandle,
+    config: &crate::config::AppConfig,
+    error: crate::ai::AiError,
+    message: &str,
+    mood: Option<&str>,
+    source: Option<&str>,
+) -> Result<String, crate::ai::AiError> {
+    use crate::ai::fallback::{FallbackVars, PhraseBank};
+
+    if !config.ai.offline_fallback || !error.is_unreachable() {
+        return Err(error);
+    }
+    tracing::info!("{}; answering from the phrase banks", error);
+
+    let bank = match app.path().app_config_dir() {
+        Ok(dir) => PhraseBank::load(&dir),
+        Err(_) => PhraseBank::bundled(),
+    };
+    let stats = app
+        .try_state::<crate::monitors::LatestStats>()
+        .and_then(|latest| latest.get());
+    let vars = FallbackVars::new(
+        stats.as_ref(),
+        mood,
+        chrono::Local::now().format("%H:%M").to_string(),
+    );
+    let typed = source.is_none_or(|s| s == "user");
+    Ok(bank.respond(typed.then_some(message), &vars, &mut rand::thread_rng()))
+}
+
+/// Tools answering from the latest monitor reading and learned app usage.
+fn system_tools(
+    app: &tauri::AppHandle,
+    config: &crate::config::AppConfig,
+) -> crate::ai::tools::SystemTools {
+    let stats = app
+        .try_state::<crate::monitors::LatestStats>()
+        .and_then(|latest| latest.get());
+    // The shared config state has the freshest usage counts.
+    let learning = app
+        .try_state::<crate::config::ConfigState>()
+        .and_then(|state| state.0.read().ok().map(|c| c.learning.clone()))
+        .unwrap_or_else(|| config.learning.clone());
+    crate::ai::tools::SystemTools::new(stats, &learning)
+}
+
+/// Registry key of a chat request started with `request_id`.
+fn chat_key(request_id: &str) -> String {
+    format!("chat:{}", request_id)
+}
+
+/// Abort the chat request started with `request_id`. Returns whether one was
+/// running; the aborted command rejects with `AiError::Cancelled`.
+#[tauri::command]
+fn cancel_chat(
+    requests: tauri::State<'_, crate::ai::cancel::CancelRegistry>,
+    request_id: String,
+) -> bool {
+    requests.cancel(&chat_key(&request_id))
+}
+
+// Arguments mirror the invoke payload sent by the frontend.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn chat_with_ethereal(
+    app: tauri::AppHandle,
+    requests: tauri::State<'_, crate::ai::cancel::CancelRegistry>,
+    message: String,
+    history: Option<Vec<crate::ai::ChatMessage>>,
+    system_context: Option<String>,
+    mood: Option<String>,
+    request_id: Option<String>,
+    source: Option<String>,
+) -> Result<String, crate::ai::AiError> {
+    use crate::config::AppConfig;
+
+    let config = AppConfig::load(&app).unwrap_or_default();
+    let cooldown = check_cooldown(&app, &config)?;
+    let registration = request_id
+        .as_deref()
+        .map(|id| requests.start(&chat_key(id)))
+        .transpose()?;
+
+    let max_chars = config.ai.max_response_length;
+    let client = crate::ai::factory::chat_provider(&app, config.ai.clone());
+    let (conversation_id, reply) =
+        crate::ai::cancel::cancellable(registration.as_ref().map(|r| r.token()), async {
+            let user_message = build_user_message(
+                &app,
+                &config,
+                message.clone(),
+                system_context,
+                mood.as_deref(),
+                source.as_deref(),
+            );
+            let user_message = add_notes(&app, &config, user_message, &message).await;
+            // Structured replies only for this command: the stream is shown
+            // as it arrives and can't be JSON.
+            let instructions = config
+                .actions
+                .enabled
+                .then(|| crate::ai::actions::format_instructions(&config.actions));
+            let (conversation_id, full_history) = compose_messages(
+                &app,
+                &config,
+                client.as_ref(),
+                user_message,
+                history,
+                mood.as_deref(),
+                instructions,
             )
-        } else {
-            format!("System Context: {}\n\nUser Message: {}", ctx, message)
+            .await;
+
+            let messages = client.build_messages(full_history, mood.as_deref());
+            let mut sampling = client.sampling(mood.as_deref());
+            if config.actions.enabled {
+                sampling = crate::ai::actions::wrapper_sampling(sampling);
+            }
+            let reply = if config.ai.enable_tools {
+                let tools = system_tools(&app, &config);
+                client
+                    .complete_with_tools(messages, sampling, &tools)
+                    .await?
+            } else {
+                client.complete(messages, sampling).await?
+            };
+            Ok((conversation_id, reply))
+        })
+        .await
+        .or_else(|e| {
+            offline_reply(
+                &app,
+                &config,
+                e,
+                &message,
+                mood.as_deref(),
+                source.as_deref(),
+            )
+            .map(|reply| (None, reply))
+        })?;
+
+    let reply = if config.actions.enabled {
+        let plan = crate::ai::actions::plan(&reply, &config.actions);
+        for rejected in &plan.rejected {
+            tracing::warn!("Dropped sprite action: {}", rejected);
         }
-    } else if !learning_context.is_empty() {
-        format!(
-            "Learned Context: {}\n\nUser Message: {}",
-            learning_context, message
-        )
+        crate::ai::actions::dispatch(&app, plan.actions, &config.actions);
+        plan.say
     } else {
-        message
+        reply
     };
+    let reply = crate::ai::limits::trim_reply(&reply, max_chars);
+    remember_turn(
+        &app,
+        conversation_id,
+        &user_text(&message, source.as_deref()),
+        &reply,
+    );
+    if let Some(permit) = cooldown {
+        permit.keep();
+    }
+    Ok(reply)
+}
 
-    full_history.push(crate::ai::ChatMessage {
-        role: "user".to_string(),
-        content: user_content,
-    });
+/// Payload of `chat-token` and `chat-done`. `request_id` lets the frontend
+/// drop fragments of a request it has cancelled or replaced.
+#[derive(Clone, serde::Serialize)]
+struct ChatFragment<'a> {
+    request_id: Option<&'a str>,
+    text: &'a str,
+}
 
-    client
-        .chat(full_history, mood.as_deref())
+/// Same as `chat_with_ethereal`, but streams the reply: every fragment is
+/// emitted as `chat-token` and the full text as `chat-done`.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn chat_with_ethereal_stream(
+    app: tauri::AppHandle,
+    requests: tauri::State<'_, crate::ai::cancel::CancelRegistry>,
+    message: String,
+    history: Option<Vec<crate::ai::ChatMessage>>,
+    system_context: Option<String>,
+    mood: Option<String>,
+    request_id: Option<String>,
+    source: Option<String>,
+) -> Result<String, crate::ai::AiError> {
+    use crate::config::AppConfig;
+
+    let config = AppConfig::load(&app).unwrap_or_default();
+    let cooldown = check_cooldown(&app, &config)?;
+    let registration = request_id
+        .as_deref()
+        .map(|id| requests.start(&chat_key(id)))
+        .transpose()?;
+
+    let max_chars = config.ai.max_response_length;
+    let client = crate::ai::factory::chat_provider(&app, config.ai.clone());
+    let (conversation_id, reply) =
+        crate::ai::cancel::cancellable(registration.as_ref().map(|r| r.token()), async {
+            let user_message = build_user_message(
+                &app,
+                &config,
+                message.clone(),
+                system_context,
+                mood.as_deref(),
+                source.as_deref(),
+            );
+            let user_message = add_notes(&app, &config, user_message, &message).await;
+            let (conversation_id, full_history) = compose_messages(
+                &app,
+                &config,
+                client.as_ref(),
+                user_message,
+                history,
+                mood.as_deref(),
+                None,
+            )
+            .await;
+
+            // Stop forwarding fragments once the reply is over the limit; the
+            // trimmed text arrives with `chat-done`.
+            let mut streamed_chars = 0;
+            let reply = client
+                .chat_stream(full_history, mood.as_deref(), &mut |token| {
+                    if max_chars > 0 && streamed_chars >= max_chars {
+                        return;
+                    }
+                    streamed_chars += token.chars().count();
+                    let fragment = ChatFragment {
+                        request_id: request_id.as_deref(),
+                        text: token,
+                    };
+                    if let Err(e) = app.emit("chat-token", fragment) {
+                        tracing::error!("Failed to emit chat-token: {}", e);
+                    }
+                })
+                .await?;
+            Ok((conversation_id, reply))
+        })
         .await
-        .map_err(|e| e.to_string())
+        .or_else(|e| {
+            let reply = offline_reply(
+                &app,
+                &config,
+                e,
+                &message,
+                mood.as_deref(),
+                source.as_deref(),
+            )?;
+            let fragment = ChatFragment {
+                request_id: request_id.as_deref(),
+                text: &reply,
+            };
+            if let Err(e) = app.emit("chat-token", fragment) {
+                tracing::error!("Failed to emit chat-token: {}", e);
+            }
+            Ok::<_, crate::ai::AiError>((None, reply))
+        })?;
+
+    let reply = crate::ai::limits::trim_reply(&reply, max_chars);
+    remember_turn(
+        &app,
+        conversation_id,
+        &user_text(&message, source.as_deref()),
+        &reply,
+    );
+    let fragment = ChatFragment {
+        request_id: request_id.as_deref(),
+        text: &reply,
+    };
+    if let Err(e) = app.emit("chat-done", fragment) {
+        tracing::error!("Failed to emit chat-done: {}", e);
+    }
+    if let Some(permit) = cooldown {
+        permit.keep();
+    }
+    Ok(reply)
+}
+
+/// Run a `clipboard-offer` action on `text` and resolve with the result.
+/// Subject to the chat cooldown; capped at `clipboard.max_result_length`.
+#[tauri::command]
+async fn run_clipboard_action(
+    app: tauri::AppHandle,
+    action: crate::ai::clipboard::ClipAction,
+    text: String,
+) -> Result<String, crate::ai::AiError> {
+    use crate::config::AppConfig;
+
+    let config = AppConfig::load(&app).unwrap_or_default();
+    let cooldown = check_cooldown(&app, &config)?;
+
+    let mut ai = config.ai.clone();
+    ai.max_response_length = config.clipboard.max_result_length;
+    let client = crate::ai::factory::chat_provider(&app, ai);
+    let result = crate::ai::clipboard::run_action(
+        client.as_ref(),
+        action,
+        &text,
+        &config.clipboard.translate_to,
+    )
+    .await?;
+    if let Some(permit) = cooldown {
+        permit.keep();
+    }
+    Ok(result)
 }
 
 #[tauri::command]
@@ -126,7 +590,32 @@ pub fn run() {
             let learning_monitor = monitors::learning::LearningMonitor::new(app.handle().clone());
             app.manage(learning_monitor);
 
+            app.manage(monitors::LatestStats::default());
+            app.manage(monitors::state::MoodOverride::default());
+            app.manage(ai::limits::ChatCooldown::new());
+            app.manage(ai::cancel::CancelRegistry::new());
+            match ai::metrics::AiMetrics::from_app(app.handle()) {
+                Ok(metrics) => {
+                    app.manage(metrics);
+                }
+                Err(e) => tracing::error!("Failed to open AI metrics: {}", e),
+            }
+            match ai::history::ConversationStore::from_app(app.handle()) {
+                Ok(store) => {
+                    app.manage(store);
+                }
+                Err(e) => tracing::error!("Failed to open conversation store: {}", e),
+            }
+            match ai::knowledge::KnowledgeBase::from_app(app.handle()) {
+                Ok(knowledge) => {
+                    app.manage(knowledge);
+                    ai::knowledge::refresh(app.handle());
+                }
+                Err(e) => tracing::error!("Failed to open knowledge base: {}", e),
+            }
+
             monitors::spawn_monitor_thread(app.handle().clone());
+            ai::health::spawn_status_watcher(app.handle().clone());
             monitors::clipboard::ClipboardMonitor::new().start_polling(app.handle().clone());
             utils::hotkeys::setup_global_hotkeys(app.handle())?;
             utils::startup::apply_config(app.handle());
@@ -144,8 +633,31 @@ pub fn run() {
             config::save_window_position,
             config::get_config,
             config::update_config,
-            chat_with_ethereal
+            chat_with_ethereal,
+            chat_with_ethereal_stream,
+            cancel_chat,
+            run_clipboard_action,
+            ai::knowledge::reindex_knowledge,
+            ai::metrics::get_ai_stats,
+            ai::health::ai_status,
+            ai::health::ai_list_models,
+            ai::pull::ai_pull_model,
+            ai::pull::ai_cancel_pull,
+            ai::persona::list_personas,
+            ai::persona::switch_persona,
+            ai::history::list_conversations,
+            ai::history::load_conversation,
+            ai::history::new_conversation,
+            ai::history::clear_conversation,
+            ai::history::export_conversation
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                if let Some(metrics) = app.try_state::<ai::metrics::AiMetrics>() {
+                    metrics.flush();
+                }
+            }
+        });
 }
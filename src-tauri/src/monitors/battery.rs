@@ -26,4 +26,12 @@ impl BatteryMonitor {
         }
         (0.0, "Unknown".to_string())
     }
+
+    /// Battery temperature in °C, where the platform reports one.
+    pub fn get_temperature(&self) -> Option<f32> {
+        let manager = Manager::new().ok()?;
+        let battery = manager.batteries().ok()?.next()?.ok()?;
+        // Kelvin.
+        battery.temperature().map(|t| t.value - 273.15)
+    }
 }
@@ -1,7 +1,8 @@
+use crate::ai::clipboard::ClipboardOffer;
 use arboard::Clipboard;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
 pub struct ClipboardMonitor {
     last_content: Arc<Mutex<String>>,
@@ -43,6 +44,7 @@ impl ClipboardMonitor {
                                     tracing::error!("Failed to emit clipboard event: {}", e);
                                 } else {
                                     tracing::info!("Clipboard changed (len: {})", text.len());
+                                    emit_offer(&app, &text);
                                     *last = text;
                                 }
                             }
@@ -57,3 +59,26 @@ impl ClipboardMonitor {
         });
     }
 }
+
+/// Classify the new clip and emit `clipboard-offer` with the actions that
+/// apply, unless clipboard actions are turned off.
+fn emit_offer(app: &AppHandle, text: &str) {
+    let Some(clipboard) = app
+        .try_state::<crate::config::ConfigState>()
+        .map(|state| state.0.read().unwrap().clipboard.clone())
+    else {
+        return;
+    };
+    if !clipboard.actions_enabled {
+        return;
+    }
+    let offer = ClipboardOffer::new(text.to_string(), &clipboard.translate_to);
+    tracing::debug!(
+        "Clipboard looks like {:?}, offering {:?}",
+        offer.kind,
+        offer.actions
+    );
+    if let Err(e) = app.emit("clipboard-offer", &offer) {
+        tracing::error!("Failed to emit clipboard offer: {}", e);
+    }
+}
@@ -0,0 +1,47 @@
+use crate::monitors::{HardwareMonitor, MetricsSnapshot};
+
+#[cfg(test)]
+#[path = "composite_test.rs"]
+mod composite_test;
+
+/// Merges a CPU monitor with a GPU monitor.
+///
+/// Temperatures from both sides are kept, each checked against its own
+/// threshold, as are CPU and GPU utilization, so either side can drive the
+/// sprite's state. VRAM and power come from the GPU; RAM, network, disk and
+/// battery from the CPU side.
+pub struct CompositeMonitor {
+    cpu: Box<dyn HardwareMonitor>,
+    gpu: Box<dyn HardwareMonitor>,
+}
+
+impl CompositeMonitor {
+    pub fn new(cpu: Box<dyn HardwareMonitor>, gpu: Box<dyn HardwareMonitor>) -> Self {
+        Self { cpu, gpu }
+    }
+}
+
+impl HardwareMonitor for CompositeMonitor {
+    fn sample(&self) -> MetricsSnapshot {
+        let cpu = self.cpu.sample();
+        let Some(gpu) = self.gpu.is_available().then(|| self.gpu.sample()) else {
+            return cpu;
+        };
+        MetricsSnapshot {
+            temperatures: cpu
+                .temperatures
+                .into_iter()
+                .chain(gpu.temperatures)
+                .collect(),
+            gpu_utilization: gpu.gpu_utilization,
+            vram: gpu.vram,
+            power_draw: gpu.power_draw,
+            ..cpu
+        }
+    }
+
+    /// The CPU side keeps the monitor alive if the GPU drops out.
+    fn is_available(&self) -> bool {
+        self.cpu.is_available() || self.gpu.is_available()
+    }
+}
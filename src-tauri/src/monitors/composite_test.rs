@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use crate::monitors::composite::CompositeMonitor;
+    use crate::monitors::{HardwareMonitor, MetricsSnapshot, Sensor, TemperatureReading};
+
+    struct FixedMonitor {
+        snapshot: MetricsSnapshot,
+        available: bool,
+    }
+
+    impl HardwareMonitor for FixedMonitor {
+        fn sample(&self) -> MetricsSnapshot {
+            self.snapshot.clone()
+        }
+        fn is_available(&self) -> bool {
+            self.available
+        }
+    }
+
+    fn cpu() -> Box<dyn HardwareMonitor> {
+        Box::new(FixedMonitor {
+            snapshot: MetricsSnapshot {
+                temperatures: vec![TemperatureReading {
+                    sensor: Sensor::Cpu,
+                    celsius: 55.0,
+                }],
+                cpu_utilization: Some(20.0),
+                memory: Some((8000, 16000)),
+                network: Some((12, 3)),
+                disk: Some((0, 0)),
+                battery: Some((80.0, "Discharging".to_string())),
+                ..MetricsSnapshot::now()
+            },
+            available: true,
+        })
+    }
+
+    fn gpu(available: bool) -> Box<dyn HardwareMonitor> {
+        Box::new(FixedMonitor {
+            snapshot: MetricsSnapshot {
+                temperatures: vec![TemperatureReading {
+                    sensor: Sensor::AmdGpu,
+                    celsius: 78.0,
+                }],
+                gpu_utilization: Some(95.0),
+                vram: Some((6000, 12000)),
+                power_draw: Some(240.0),
+                ..MetricsSnapshot::now()
+            },
+            available,
+        })
+    }
+
+    #[test]
+    fn test_merges_cpu_and_gpu() {
+        let sample = CompositeMonitor::new(cpu(), gpu(true)).sample();
+        assert_eq!(sample.temperatures.len(), 2);
+        assert_eq!(sample.temperature(), Some(78.0));
+        assert_eq!(sample.cpu_utilization, Some(20.0));
+        assert_eq!(sample.gpu_utilization, Some(95.0));
+        assert_eq!(sample.utilization(), Some(95.0));
+        assert_eq!(sample.memory, Some((8000, 16000)));
+        assert_eq!(sample.vram, Some((6000, 12000)));
+        assert_eq!(sample.power_draw, Some(240.0));
+        assert_eq!(sample.network, Some((12, 3)));
+        assert_eq!(sample.battery.unwrap().1, "Discharging");
+    }
+
+    #[test]
+    fn test_falls_back_to_cpu_when_gpu_drops_out() {
+        let monitor = CompositeMonitor::new(cpu(), gpu(false));
+        assert!(monitor.is_available());
+        let sample = monitor.sample();
+        assert_eq!(sample.temperature(), Some(55.0));
+        assert_eq!(sample.utilization(), Some(20.0));
+        assert_eq!(sample.gpu_utilization, None);
+        assert_eq!(sample.memory, Some((8000, 16000)));
+        assert_eq!(sample.vram, None);
+        assert_eq!(sample.power_draw, None);
+    }
+}
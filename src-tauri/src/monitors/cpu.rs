@@ -1,6 +1,6 @@
 use crate::monitors::battery::BatteryMonitor;
 use crate::monitors::network::NetworkMonitor;
-use crate::monitors::HardwareMonitor;
+use crate::monitors::{HardwareMonitor, MetricsSnapshot, Sensor, TemperatureReading};
 use std::sync::{Arc, Mutex};
 use sysinfo::{
     Components, CpuRefreshKind, MemoryRefreshKind, ProcessRefreshKind, RefreshKind, System,
@@ -8,12 +8,18 @@ use sysinfo::{
 
 use std::time::{Duration, Instant};
 
+#[cfg(test)]
+#[path = "cpu_test.rs"]
+mod cpu_test;
+
 pub struct CpuMonitor {
     sys: Arc<Mutex<System>>,
     net: NetworkMonitor,
     bat: Option<BatteryMonitor>,
     last_disk_refresh: Mutex<Instant>,
     last_disk_stats: Mutex<(u64, u64)>,
+    /// Lowercased label substrings of the sensors that count as the CPU.
+    sensor_labels: Vec<String>,
 }
 
 impl Default for CpuMonitor {
@@ -24,6 +30,12 @@ impl Default for CpuMonitor {
 
 impl CpuMonitor {
     pub fn new() -> Self {
+        Self::with_sensor_labels(&crate::config::default_cpu_sensor_labels())
+    }
+
+    /// Average the temperature sensors whose label contains one of `labels`,
+    /// ignoring case.
+    pub fn with_sensor_labels(labels: &[String]) -> Self {
         let mut sys = System::new_with_specifics(
             RefreshKind::new()
                 .with_cpu(CpuRefreshKind::everything())
@@ -39,39 +51,38 @@ impl CpuMonitor {
             bat: BatteryMonitor::new().ok(),
             last_disk_refresh: Mutex::new(Instant::now() - Duration::from_secs(60)),
             last_disk_stats: Mutex::new((0, 0)),
+            sensor_labels: labels
+                .iter()
+                .map(|l| l.trim().to_lowercase())
+                .filter(|l| !l.is_empty())
+                .collect(),
         }
     }
-}
 
-impl HardwareMonitor for CpuMonitor {
-    fn get_temperature(&self) -> f32 {
+    /// Average of the configured CPU sensors.
+    fn temperature(&self) -> Option<f32> {
         let components = Components::new_with_refreshed_list();
 
         let mut sum = 0.0;
         let mut count = 0;
 
         for component in &components {
-            let label = component.label().to_lowercase();
-            if label.contains("cpu") || label.contains("package") || label.contains("core") {
+            if matches_label(&component.label().to_lowercase(), &self.sensor_labels) {
                 sum += component.temperature();
                 count += 1;
             }
         }
 
-        if count > 0 {
-            sum / count as f32
-        } else {
-            0.0
-        }
+        (count > 0).then(|| sum / count as f32)
     }
 
-    fn get_utilization(&self) -> f32 {
+    fn utilization(&self) -> f32 {
         let mut sys = self.sys.lock().unwrap();
         sys.refresh_cpu_usage();
         sys.global_cpu_usage()
     }
 
-    fn get_memory_usage(&self) -> (u64, u64) {
+    fn memory_usage(&self) -> (u64, u64) {
         let mut sys = self.sys.lock().unwrap();
         sys.refresh_memory();
         (
@@ -80,11 +91,7 @@ impl HardwareMonitor for CpuMonitor {
         )
     }
 
-    fn get_network_usage(&self) -> (u64, u64) {
-        self.net.get_usage()
-    }
-
-    fn get_disk_usage(&self) -> (u64, u64) {
+    fn disk_usage(&self) -> (u64, u64) {
         let now = Instant::now();
         let mut last_refresh = self.last_disk_refresh.lock().unwrap();
 
@@ -114,12 +121,38 @@ impl HardwareMonitor for CpuMonitor {
         *self.last_disk_stats.lock().unwrap() = stats;
         stats
     }
+}
 
-    fn get_battery_status(&self) -> (f32, String) {
-        if let Some(ref bat) = self.bat {
-            bat.get_status()
-        } else {
-            (0.0, "N/A".to_string())
+/// Whether a lowercased sensor label contains one of the lowercased patterns.
+/// An empty pattern matches nothing rather than every sensor.
+fn matches_label(label: &str, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| !pattern.is_empty() && label.contains(pattern.as_str()))
+}
+
+impl HardwareMonitor for CpuMonitor {
+    fn sample(&self) -> MetricsSnapshot {
+        let cpu = self.temperature().map(|celsius| TemperatureReading {
+            sensor: Sensor::Cpu,
+            celsius,
+        });
+        let battery = self
+            .bat
+            .as_ref()
+            .and_then(|bat| bat.get_temperature())
+            .map(|celsius| TemperatureReading {
+                sensor: Sensor::Battery,
+                celsius,
+            });
+        MetricsSnapshot {
+            temperatures: cpu.into_iter().chain(battery).collect(),
+            cpu_utilization: Some(self.utilization()),
+            memory: Some(self.memory_usage()),
+            network: Some(self.net.get_usage()),
+            disk: Some(self.disk_usage()),
+            battery: self.bat.as_ref().map(|bat| bat.get_status()),
+            ..MetricsSnapshot::now()
         }
     }
 
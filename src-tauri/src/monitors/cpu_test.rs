@@ -0,0 +1,12 @@
+#[cfg(test)]
+mod tests {
+    use crate::monitors::cpu::matches_label;
+
+    #[test]
+    fn test_matches_label_ignores_empty_patterns() {
+        let patterns = vec!["k10temp".to_string(), String::new()];
+        assert!(matches_label("k10temp tctl", &patterns));
+        assert!(!matches_label("nvme composite", &patterns));
+        assert!(!matches_label("acpitz", &[String::new()]));
+    }
+}
@@ -1,16 +1,74 @@
+use crate::config::HardwareConfig;
 use crate::monitors::{
+    composite::CompositeMonitor,
     cpu::CpuMonitor,
     mock::{ActivityPattern, MockDataGenerator},
+    sysfs_gpu::SysfsGpuMonitor,
     HardwareMonitor,
 };
 
-pub fn create_monitor() -> Box<dyn HardwareMonitor> {
-    if cfg!(debug_assertions) {
-        if std::env::var("ETHEREAL_USE_MOCK").is_ok() {
-            return Box::new(MockDataGenerator::new(ActivityPattern::Fluctuating));
+/// Accepted values of `hardware.monitor_source`.
+pub const MONITOR_SOURCES: &[&str] = &["auto", "cpu", "nvidia", "amd", "composite", "mock"];
+
+/// Build the monitor selected by `config.monitor_source`.
+///
+/// - `auto`: CPU merged with the first GPU found, or the CPU alone.
+/// - `cpu`, `nvidia`, `amd`: that device alone. `amd` also covers Intel
+///   GPUs, which share the sysfs reader.
+/// - `composite`: CPU merged with the first GPU found.
+/// - `mock`: generated data.
+///
+/// A GPU that can't be opened falls back to the CPU monitor. Debug builds
+/// still honor `ETHEREAL_USE_MOCK`.
+pub fn create_monitor(config: &HardwareConfig) -> Box<dyn HardwareMonitor> {
+    if cfg!(debug_assertions) && std::env::var("ETHEREAL_USE_MOCK").is_ok() {
+        return mock();
+    }
+
+    let cpu = || -> Box<dyn HardwareMonitor> {
+        Box::new(CpuMonitor::with_sensor_labels(&config.cpu_sensor_labels))
+    };
+    match config.monitor_source.as_str() {
+        "cpu" => cpu(),
+        "mock" => mock(),
+        "nvidia" => nvidia().unwrap_or_else(|| cpu_fallback("nvidia", cpu())),
+        "amd" => sysfs_gpu().unwrap_or_else(|| cpu_fallback("amd", cpu())),
+        "composite" => composite(cpu).unwrap_or_else(|| cpu_fallback("composite", cpu())),
+        "auto" => composite(cpu).unwrap_or_else(cpu),
+        other => {
+            tracing::warn!("Unknown monitor source '{}', using auto", other);
+            composite(cpu).unwrap_or_else(cpu)
         }
-        Box::new(CpuMonitor::new())
-    } else {
-        Box::new(CpuMonitor::new())
     }
 }
+
+fn mock() -> Box<dyn HardwareMonitor> {
+    Box::new(MockDataGenerator::new(ActivityPattern::Fluctuating))
+}
+
+fn cpu_fallback(source: &str, cpu: Box<dyn HardwareMonitor>) -> Box<dyn HardwareMonitor> {
+    tracing::warn!(
+        "No GPU available for monitor source '{}', using CPU",
+        source
+    );
+    cpu
+}
+
+fn composite(cpu: impl Fn() -> Box<dyn HardwareMonitor>) -> Option<Box<dyn HardwareMonitor>> {
+    let gpu = nvidia().or_else(sysfs_gpu)?;
+    Some(Box::new(CompositeMonitor::new(cpu(), gpu)))
+}
+#[cfg(feature = "nvidia-support")]
+fn nvidia() -> Option<Box<dyn HardwareMonitor>> {
+    crate::monitors::nvidia::NvidiaMonitor::new().map(|m| Box::new(m) as Box<dyn HardwareMonitor>)
+}
+
+#[cfg(not(feature = "nvidia-support"))]
+fn nvidia() -> Option<Box<dyn HardwareMonitor>> {
+    tracing::info!("Built without nvidia-support; skipping NVIDIA monitoring");
+    None
+}
+
+fn sysfs_gpu() -> Option<Box<dyn HardwareMonitor>> {
+    SysfsGpuMonitor::new().map(|m| Box::new(m) as Box<dyn HardwareMonitor>)
+}
@@ -1,4 +1,4 @@
-use crate::monitors::HardwareMonitor;
+use crate::monitors::{HardwareMonitor, MetricsSnapshot, Sensor, TemperatureReading};
 use rand::Rng;
 
 #[cfg(test)]
@@ -52,24 +52,8 @@ impl MockDataGenerator {
             },
         }
     }
-}
-
-impl HardwareMonitor for MockDataGenerator {
-    fn get_temperature(&self) -> f32 {
-        self.generate_gpu_data().temperature
-    }
 
-    fn get_utilization(&self) -> f32 {
-        self.generate_gpu_data().utilization
-    }
-
-    fn get_memory_usage(&self) -> (u64, u64) {
-        let data = self.generate_gpu_data();
-        (data.memory_used, data.memory_total)
-    }
-
-    fn get_network_usage(&self) -> (u64, u64) {
-        let mut rng = rand::thread_rng();
+    fn network_usage(&self, rng: &mut impl Rng) -> (u64, u64) {
         match self.pattern {
             ActivityPattern::Idle => (rng.gen_range(0..10), rng.gen_range(0..5)),
             ActivityPattern::HighLoad => (rng.gen_range(1000..5000), rng.gen_range(500..2000)),
@@ -77,18 +61,33 @@ impl HardwareMonitor for MockDataGenerator {
         }
     }
 
-    fn get_disk_usage(&self) -> (u64, u64) {
-        let mut rng = rand::thread_rng();
+    fn disk_usage(&self, rng: &mut impl Rng) -> (u64, u64) {
         match self.pattern {
             ActivityPattern::Idle => (rng.gen_range(0..50), rng.gen_range(0..20)),
             ActivityPattern::HighLoad => (rng.gen_range(10000..50000), rng.gen_range(5000..20000)),
             ActivityPattern::Fluctuating => (rng.gen_range(100..5000), rng.gen_range(50..2000)),
         }
     }
+}
 
-    fn get_battery_status(&self) -> (f32, String) {
+impl HardwareMonitor for MockDataGenerator {
+    /// One generated reading; every field comes from the same draw.
+    fn sample(&self) -> MetricsSnapshot {
         let mut rng = rand::thread_rng();
-        (rng.gen_range(20.0..100.0), "Discharging".to_string())
+        let gpu = self.generate_gpu_data();
+        MetricsSnapshot {
+            temperatures: vec![TemperatureReading {
+                sensor: Sensor::NvidiaGpu,
+                celsius: gpu.temperature,
+            }],
+            gpu_utilization: Some(gpu.utilization),
+            memory: Some((rng.gen_range(4000..12000), 16384)),
+            vram: Some((gpu.memory_used, gpu.memory_total)),
+            network: Some(self.network_usage(&mut rng)),
+            disk: Some(self.disk_usage(&mut rng)),
+            battery: Some((rng.gen_range(20.0..100.0), "Discharging".to_string())),
+            ..MetricsSnapshot::now()
+        }
     }
 
     fn is_available(&self) -> bool {
@@ -2,6 +2,7 @@
 mod tests {
 
     use crate::monitors::mock::{ActivityPattern, MockDataGenerator};
+    use crate::monitors::HardwareMonitor;
 
     #[test]
     fn test_mock_gpu_data_generation() {
@@ -22,4 +23,18 @@ mod tests {
         assert!(data.utilization >= 90.0 && data.utilization < 100.0);
         assert!(data.memory_used >= 15000 && data.memory_used < 20000);
     }
+
+    #[test]
+    fn test_sample_is_one_consistent_reading() {
+        let sample = MockDataGenerator::new(ActivityPattern::HighLoad).sample();
+        let temperature = sample.temperature().unwrap();
+        let utilization = sample.gpu_utilization.unwrap();
+        let (used, total) = sample.vram.unwrap();
+        assert!((75.0..85.0).contains(&temperature));
+        assert!((90.0..100.0).contains(&utilization));
+        assert!((15000..20000).contains(&used));
+        assert_eq!(total, 24576);
+        assert_eq!(sample.utilization(), Some(utilization));
+        assert!(sample.network.is_some() && sample.disk.is_some());
+    }
 }
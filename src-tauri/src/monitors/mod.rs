@@ -1,74 +1,202 @@
 pub mod battery;
 pub mod clipboard;
+pub mod composite;
 pub mod cpu;
 pub mod factory;
 pub mod learning;
 pub mod mock;
 pub mod network;
+#[cfg(feature = "nvidia-support")]
+pub mod nvidia;
 pub mod state;
+pub mod sysfs_gpu;
 pub mod window;
 
+/// Where a temperature reading comes from. Each kind has its own overheat
+/// threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sensor {
+    Cpu,
+    NvidiaGpu,
+    AmdGpu,
+    IntelGpu,
+    Battery,
+}
+
+impl Sensor {
+    pub fn threshold(self, thresholds: &ThresholdsConfig) -> f32 {
+        match self {
+            Sensor::Cpu => thresholds.cpu_temp,
+            Sensor::NvidiaGpu => thresholds.nvidia_temp,
+            Sensor::AmdGpu | Sensor::IntelGpu => thresholds.amd_temp,
+            Sensor::Battery => thresholds.battery_temp,
+        }
+    }
+
+    /// Human-readable name, e.g. for notifications.
+    pub fn label(self) -> &'static str {
+        match self {
+            Sensor::Cpu => "CPU",
+            Sensor::NvidiaGpu => "NVIDIA GPU",
+            Sensor::AmdGpu => "AMD GPU",
+            Sensor::IntelGpu => "Intel GPU",
+            Sensor::Battery => "battery",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TemperatureReading {
+    pub sensor: Sensor,
+    pub celsius: f32,
+}
+
+/// Every metric a monitor reads in one tick. Sensors the monitor doesn't
+/// have, or couldn't read this time, are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetricsSnapshot {
+    pub timestamp: DateTime<Local>,
+    /// One reading per temperature sensor the monitor watches.
+    pub temperatures: Vec<TemperatureReading>,
+    /// Percent.
+    pub cpu_utilization: Option<f32>,
+    /// Percent.
+    pub gpu_utilization: Option<f32>,
+    /// System RAM (used, total) in MB.
+    pub memory: Option<(u64, u64)>,
+    /// GPU memory (used, total) in MB.
+    pub vram: Option<(u64, u64)>,
+    /// (rx, tx) in KB/s.
+    pub network: Option<(u64, u64)>,
+    /// (read, write) in KB.
+    pub disk: Option<(u64, u64)>,
+    /// (level percent, state).
+    pub battery: Option<(f32, String)>,
+    /// GPU board power in watts.
+    pub power_draw: Option<f32>,
+}
+
+impl MetricsSnapshot {
+    /// An empty snapshot stamped with the current time.
+    pub fn now() -> Self {
+        Self {
+            timestamp: Local::now(),
+            ..Self::default()
+        }
+    }
+
+    /// The hottest reading in °C.
+    pub fn temperature(&self) -> Option<f32> {
+        self.temperatures
+            .iter()
+            .map(|reading| reading.celsius)
+            .reduce(f32::max)
+    }
+
+    /// The busier of CPU and GPU utilization.
+    pub fn utilization(&self) -> Option<f32> {
+        match (self.cpu_utilization, self.gpu_utilization) {
+            (Some(cpu), Some(gpu)) => Some(cpu.max(gpu)),
+            (cpu, gpu) => cpu.or(gpu),
+        }
+    }
+
+    /// RAM use as a percentage of the total, or VRAM use for monitors
+    /// that only see the GPU.
+    pub fn memory_pressure(&self) -> Option<f32> {
+        match self.memory.or(self.vram) {
+            Some((used, total)) if total > 0 => Some(used as f32 / total as f32 * 100.0),
+            _ => None,
+        }
+    }
+}
+
 pub trait HardwareMonitor: Send + Sync {
-    fn get_temperature(&self) -> f32;
-    fn get_utilization(&self) -> f32;
-    fn get_memory_usage(&self) -> (u64, u64);
-    fn get_network_usage(&self) -> (u64, u64);
-    fn get_disk_usage(&self) -> (u64, u64);
-    fn get_battery_status(&self) -> (f32, String);
+    /// Read every supported sensor once.
+    fn sample(&self) -> MetricsSnapshot;
     fn is_available(&self) -> bool;
 }
 
-use crate::config::AppConfig;
+use crate::ai::commentary::{spawn_remark, CommentaryEngine, SystemClock};
+use crate::config::{AppConfig, ThresholdsConfig};
 use crate::monitors::factory::create_monitor;
-use crate::monitors::state::{determine_mood, determine_state, Mood, SpriteState};
+use crate::monitors::state::{
+    determine_mood, determine_state, overheating_sensor, Mood, MoodOverride, SpriteState,
+};
 use crate::monitors::window::WindowMonitor;
 use crate::utils::notification::send_notification;
+use chrono::{DateTime, Local};
 use serde::Serialize;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
 
 #[derive(Clone, Serialize)]
-struct GpuStats {
-    temperature: f32,
-    utilization: f32,
-    memory_used: u64,
-    memory_total: u64,
-    network_rx: u64,
-    network_tx: u64,
-    disk_read: u64,
-    disk_write: u64,
-    battery_level: f32,
-    battery_state: String,
-    active_window: String,
-    state: String,
-    mood: String,
+pub struct GpuStats {
+    pub temperature: f32,
+    pub utilization: f32,
+    pub cpu_utilization: Option<f32>,
+    pub gpu_utilization: Option<f32>,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    /// GPU memory (used, total) in MB.
+    pub vram: Option<(u64, u64)>,
+    pub network_rx: u64,
+    pub network_tx: u64,
+    pub disk_read: u64,
+    pub disk_write: u64,
+    pub battery_level: f32,
+    pub battery_state: String,
+    /// Watts; `None` when the monitor can't read it.
+    pub power_draw: Option<f32>,
+    /// The sensor over its threshold while the state is `Overheating`.
+    pub overheating_sensor: Option<Sensor>,
+    pub active_window: String,
+    pub active_category: String,
+    pub state: String,
+    pub mood: String,
+}
+
+/// The most recent `gpu-update` payload, for commands that need live readings.
+#[derive(Default)]
+pub struct LatestStats(pub Mutex<Option<GpuStats>>);
+
+impl LatestStats {
+    pub fn get(&self) -> Option<GpuStats> {
+        self.0.lock().unwrap().clone()
+    }
 }
 
 pub fn spawn_monitor_thread(app: AppHandle) {
     std::thread::spawn(move || {
-        let monitor = create_monitor();
+        let mut hardware = load_config(&app).hardware;
+        let mut monitor = create_monitor(&hardware);
         let window_monitor = WindowMonitor::new();
         let mut last_overheat_notif = Instant::now() - Duration::from_secs(300);
         let mut last_angry_notif = Instant::now() - Duration::from_secs(300);
         let mut last_low_battery_notif = Instant::now() - Duration::from_secs(300);
+        let mut commentary = CommentaryEngine::new(Arc::new(SystemClock));
 
         loop {
             let mut sleep_ms = 2000;
+            let config = load_config(&app);
 
-            if monitor.is_available() {
-                // Use cached config from state
-                let config = if let Some(state) = app.try_state::<crate::config::ConfigState>() {
-                    state.0.read().unwrap().clone()
-                } else {
-                    AppConfig::load(&app).unwrap_or_default()
-                };
+            if config.hardware.monitor_source != hardware.monitor_source
+                || config.hardware.cpu_sensor_labels != hardware.cpu_sensor_labels
+            {
+                tracing::info!(
+                    "Monitor settings changed, switching to '{}'",
+                    config.hardware.monitor_source
+                );
+                monitor = create_monitor(&config.hardware);
+                hardware = config.hardware.clone();
+            }
 
+            if monitor.is_available() {
                 sleep_ms = config.hardware.polling_interval_ms;
 
-                let (used, total) = monitor.get_memory_usage();
-                let (rx, tx) = monitor.get_network_usage();
-                let (read, write) = monitor.get_disk_usage();
-                let (bat_lvl, bat_state) = monitor.get_battery_status();
+                let snapshot = monitor.sample();
                 let category = window_monitor.get_active_app_category();
                 let window_title = if config.privacy.share_window_title {
                     window_monitor.get_active_window_title()
@@ -76,9 +204,13 @@ pub fn spawn_monitor_thread(app: AppHandle) {
                     "Hidden (Privacy)".to_string()
                 };
 
-                let state =
-                    determine_state(monitor.as_ref(), rx, tx, read, write, category, &config);
-                let mood = determine_mood(&state, monitor.get_utilization(), &config);
+                let hot_sensor = overheating_sensor(&snapshot, &config.hardware.thresholds);
+                let state = determine_state(&snapshot, category, &config);
+                let utilization = snapshot.utilization().unwrap_or(0.0);
+                let mood = app
+                    .try_state::<MoodOverride>()
+                    .and_then(|o| o.current(Instant::now()))
+                    .unwrap_or_else(|| determine_mood(&state, utilization, &config));
 
                 // Adaptive polling based on state
                 match state {
@@ -93,10 +225,11 @@ pub fn spawn_monitor_thread(app: AppHandle) {
                         && config.notifications.notify_on_overheating
                         && last_overheat_notif.elapsed() > Duration::from_secs(300)
                     {
+                        let sensor = hot_sensor.map_or("system", Sensor::label);
                         send_notification(
                             &app,
                             "Ethereal: Hot Hot Hot!",
-                            "The system is getting too hot. I'm melting!",
+                            &format!("The {} is getting too hot. I'm melting!", sensor),
                         );
                         last_overheat_notif = Instant::now();
                     }
@@ -126,18 +259,39 @@ pub fn spawn_monitor_thread(app: AppHandle) {
                     }
                 }
 
+                if let Some(trigger) = commentary.observe(&state, &config) {
+                    spawn_remark(
+                        app.clone(),
+                        config.ai.clone(),
+                        trigger,
+                        format!("{:?}", mood),
+                    );
+                }
+
+                let temperature = snapshot.temperature().unwrap_or(0.0);
+                let (memory_used, memory_total) = snapshot.memory.unwrap_or((0, 0));
+                let (network_rx, network_tx) = snapshot.network.unwrap_or((0, 0));
+                let (disk_read, disk_write) = snapshot.disk.unwrap_or((0, 0));
+                let (battery_level, battery_state) =
+                    snapshot.battery.unwrap_or((0.0, "N/A".to_string()));
                 let stats = GpuStats {
-                    temperature: monitor.get_temperature(),
-                    utilization: monitor.get_utilization(),
-                    memory_used: used,
-                    memory_total: total,
-                    network_rx: rx,
-                    network_tx: tx,
-                    disk_read: read,
-                    disk_write: write,
-                    battery_level: bat_lvl,
-                    battery_state: bat_state,
+                    temperature,
+                    utilization,
+                    cpu_utilization: snapshot.cpu_utilization,
+                    gpu_utilization: snapshot.gpu_utilization,
+                    memory_used,
+                    memory_total,
+                    vram: snapshot.vram,
+                    network_rx,
+                    network_tx,
+                    disk_read,
+                    disk_write,
+                    battery_level,
+                    battery_state,
+                    power_draw: snapshot.power_draw,
+                    overheating_sensor: hot_sensor,
                     active_window: window_title.clone(),
+                    active_category: format!("{:?}", category),
                     state: format!("{:?}", state),
                     mood: format!("{:?}", mood),
                 };
@@ -152,6 +306,10 @@ pub fn spawn_monitor_thread(app: AppHandle) {
                     }
                 }
 
+                if let Some(latest) = app.try_state::<LatestStats>() {
+                    *latest.0.lock().unwrap() = Some(stats.clone());
+                }
+
                 if let Err(e) = app.emit("gpu-update", stats) {
                     tracing::error!("Failed to emit gpu-update: {}", e);
                 }
@@ -160,3 +318,12 @@ pub fn spawn_monitor_thread(app: AppHandle) {
         }
     });
 }
+
+/// The cached config from state, or the file when state isn't managed yet.
+fn load_config(app: &AppHandle) -> AppConfig {
+    if let Some(state) = app.try_state::<crate::config::ConfigState>() {
+        state.0.read().unwrap().clone()
+    } else {
+        AppConfig::load(app).unwrap_or_default()
+    }
+}
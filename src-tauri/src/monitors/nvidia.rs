@@ -0,0 +1,122 @@
+use crate::monitors::{HardwareMonitor, MetricsSnapshot, Sensor, TemperatureReading};
+use nvml_wrapper::{enum_wrappers::device::TemperatureSensor, Nvml};
+
+#[cfg(test)]
+#[path = "nvidia_test.rs"]
+mod nvidia_test;
+
+/// The readings [`NvidiaMonitor`] needs from a GPU. `None` when the driver
+/// can't provide one.
+pub(crate) trait GpuDevice: Send + Sync {
+    fn name(&self) -> Option<String>;
+    /// Core temperature in °C.
+    fn temperature(&self) -> Option<u32>;
+    /// Busy percentage over the driver's last sample period.
+    fn utilization(&self) -> Option<u32>;
+    /// Used and total VRAM in bytes.
+    fn memory(&self) -> Option<(u64, u64)>;
+    /// Board power draw in milliwatts.
+    fn power_usage(&self) -> Option<u32>;
+}
+
+/// An NVML device, looked up by index on every read since NVML handles
+/// borrow the library instance.
+struct NvmlDevice {
+    nvml: Nvml,
+    index: u32,
+}
+
+impl NvmlDevice {
+    fn read<T>(
+        &self,
+        f: impl FnOnce(&nvml_wrapper::Device) -> Result<T, nvml_wrapper::error::NvmlError>,
+    ) -> Option<T> {
+        let device = self.nvml.device_by_index(self.index).ok()?;
+        f(&device).ok()
+    }
+}
+
+impl GpuDevice for NvmlDevice {
+    fn name(&self) -> Option<String> {
+        self.read(|d| d.name())
+    }
+
+    fn temperature(&self) -> Option<u32> {
+        self.read(|d| d.temperature(TemperatureSensor::Gpu))
+    }
+
+    fn utilization(&self) -> Option<u32> {
+        self.read(|d| d.utilization_rates()).map(|u| u.gpu)
+    }
+
+    fn memory(&self) -> Option<(u64, u64)> {
+        self.read(|d| d.memory_info()).map(|m| (m.used, m.total))
+    }
+
+    fn power_usage(&self) -> Option<u32> {
+        self.read(|d| d.power_usage())
+    }
+}
+
+/// Reads the first NVIDIA GPU through NVML.
+///
+/// Only GPU figures are reported; network, disk and battery are `None`.
+pub struct NvidiaMonitor {
+    device: Box<dyn GpuDevice>,
+}
+
+impl NvidiaMonitor {
+    /// Monitor GPU 0, or `None` when the NVIDIA driver or NVML library is
+    /// missing or there is no GPU.
+    pub fn new() -> Option<Self> {
+        let nvml = match Nvml::init() {
+            Ok(nvml) => nvml,
+            Err(e) => {
+                tracing::info!("NVIDIA monitoring unavailable: {}", e);
+                return None;
+            }
+        };
+        if nvml.device_count().unwrap_or(0) == 0 {
+            tracing::info!("NVML found no NVIDIA GPU");
+            return None;
+        }
+        let monitor = Self::with_device(Box::new(NvmlDevice { nvml, index: 0 }));
+        tracing::info!(
+            "Monitoring NVIDIA GPU {}",
+            monitor.device.name().unwrap_or_default()
+        );
+        Some(monitor)
+    }
+
+    pub(crate) fn with_device(device: Box<dyn GpuDevice>) -> Self {
+        Self { device }
+    }
+}
+
+impl HardwareMonitor for NvidiaMonitor {
+    fn sample(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            temperatures: self
+                .device
+                .temperature()
+                .map(|t| TemperatureReading {
+                    sensor: Sensor::NvidiaGpu,
+                    celsius: t as f32,
+                })
+                .into_iter()
+                .collect(),
+            gpu_utilization: self.device.utilization().map(|u| u as f32),
+            vram: self
+                .device
+                .memory()
+                .map(|(used, total)| (used / 1024 / 1024, total / 1024 / 1024)),
+            power_draw: self.device.power_usage().map(|mw| mw as f32 / 1000.0),
+            ..MetricsSnapshot::now()
+        }
+    }
+
+    /// False once the GPU stops answering, e.g. after a driver reset.
+    fn is_available(&self) -> bool {
+        self.device.temperature().is_some() || self.device.utilization().is_some()
+    }
+}
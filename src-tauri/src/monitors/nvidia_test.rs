@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use crate::monitors::nvidia::{GpuDevice, NvidiaMonitor};
+    use crate::monitors::{HardwareMonitor, Sensor, TemperatureReading};
+
+    /// Fixed readings; `None` fields act like an unsupported query.
+    #[derive(Default)]
+    struct FakeDevice {
+        temperature: Option<u32>,
+        utilization: Option<u32>,
+        memory: Option<(u64, u64)>,
+        power_usage: Option<u32>,
+    }
+
+    impl GpuDevice for FakeDevice {
+        fn name(&self) -> Option<String> {
+            Some("Fake RTX".to_string())
+        }
+        fn temperature(&self) -> Option<u32> {
+            self.temperature
+        }
+        fn utilization(&self) -> Option<u32> {
+            self.utilization
+        }
+        fn memory(&self) -> Option<(u64, u64)> {
+            self.memory
+        }
+        fn power_usage(&self) -> Option<u32> {
+            self.power_usage
+        }
+    }
+
+    #[test]
+    fn test_reports_device_readings() {
+        let monitor = NvidiaMonitor::with_device(Box::new(FakeDevice {
+            temperature: Some(71),
+            utilization: Some(88),
+            memory: Some((3 * 1024 * 1024 * 1024, 12 * 1024 * 1024 * 1024)),
+            power_usage: Some(215_500),
+        }));
+        assert!(monitor.is_available());
+        let sample = monitor.sample();
+        assert_eq!(
+            sample.temperatures,
+            vec![TemperatureReading {
+                sensor: Sensor::NvidiaGpu,
+                celsius: 71.0,
+            }]
+        );
+        assert_eq!(sample.gpu_utilization, Some(88.0));
+        assert_eq!(sample.vram, Some((3072, 12288)));
+        assert_eq!(sample.memory, None);
+        assert_eq!(sample.power_draw, Some(215.5));
+        assert_eq!(sample.cpu_utilization, None);
+        assert_eq!(sample.battery, None);
+    }
+
+    #[test]
+    fn test_missing_readings_are_none() {
+        let monitor = NvidiaMonitor::with_device(Box::new(FakeDevice {
+            utilization: Some(5),
+            ..FakeDevice::default()
+        }));
+        assert!(monitor.is_available());
+        let sample = monitor.sample();
+        assert_eq!(sample.gpu_utilization, Some(5.0));
+        assert!(sample.temperatures.is_empty());
+        assert_eq!(sample.vram, None);
+        assert_eq!(sample.power_draw, None);
+    }
+
+    #[test]
+    fn test_unresponsive_gpu_is_unavailable() {
+        let monitor = NvidiaMonitor::with_device(Box::new(FakeDevice::default()));
+        assert!(!monitor.is_available());
+    }
+}
@@ -1,7 +1,9 @@
-use crate::config::AppConfig;
-use crate::monitors::{window::AppCategory, HardwareMonitor};
+use crate::config::{AppConfig, ThresholdsConfig};
+use crate::monitors::{window::AppCategory, MetricsSnapshot, Sensor};
 use chrono::{Local, NaiveTime};
 use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum SpriteState {
@@ -27,6 +29,49 @@ pub enum Mood {
     Sleeping,
 }
 
+impl Mood {
+    pub const ALL: [Mood; 8] = [
+        Mood::Happy,
+        Mood::Excited,
+        Mood::Tired,
+        Mood::Bored,
+        Mood::Angry,
+        Mood::Sad,
+        Mood::Curious,
+        Mood::Sleeping,
+    ];
+
+    /// Parse a mood by name, ignoring case ("happy", "Tired", ...).
+    pub fn from_name(name: &str) -> Option<Mood> {
+        Self::ALL
+            .into_iter()
+            .find(|mood| format!("{:?}", mood).eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// A mood set from outside the monitor loop (e.g. by the model), which
+/// replaces the computed one until it expires.
+#[derive(Default)]
+pub struct MoodOverride(Mutex<Option<(Mood, Instant)>>);
+
+impl MoodOverride {
+    pub fn set(&self, mood: Mood, duration: Duration, now: Instant) {
+        *self.0.lock().unwrap() = Some((mood, now + duration));
+    }
+
+    /// The overriding mood, if one is set and hasn't expired by `now`.
+    pub fn current(&self, now: Instant) -> Option<Mood> {
+        let mut slot = self.0.lock().unwrap();
+        match slot.as_ref() {
+            Some((mood, until)) if now < *until => Some(mood.clone()),
+            _ => {
+                *slot = None;
+                None
+            }
+        }
+    }
+}
+
 pub fn is_within_sleep_time(start: &str, end: &str, now: NaiveTime) -> bool {
     let start_time = NaiveTime::parse_from_str(start, "%H:%M")
         .unwrap_or_else(|_| NaiveTime::from_hms_opt(23, 0, 0).unwrap());
@@ -59,27 +104,36 @@ pub fn determine_mood(state: &SpriteState, usage: f32, config: &AppConfig) -> Mo
     }
 }
 
+/// The sensor furthest over its own threshold, if any is over.
+pub fn overheating_sensor(
+    snapshot: &MetricsSnapshot,
+    thresholds: &ThresholdsConfig,
+) -> Option<Sensor> {
+    snapshot
+        .temperatures
+        .iter()
+        .map(|reading| {
+            (
+                reading.sensor,
+                reading.celsius - reading.sensor.threshold(thresholds),
+            )
+        })
+        .filter(|(_, excess)| *excess > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(sensor, _)| sensor)
+}
+
 pub fn determine_state(
-    monitor: &dyn HardwareMonitor,
-    network_rx: u64,
-    network_tx: u64,
-    disk_read: u64,
-    disk_write: u64,
+    snapshot: &MetricsSnapshot,
     app_category: AppCategory,
     config: &AppConfig,
 ) -> SpriteState {
-    let temp = monitor.get_temperature();
-    let usage = monitor.get_utilization();
-    let (mem_used, mem_total) = monitor.get_memory_usage();
-    let (bat_lvl, _bat_state) = monitor.get_battery_status();
-
-    let mem_pressure = if mem_total > 0 {
-        (mem_used as f32 / mem_total as f32) * 100.0
-    } else {
-        0.0
-    };
+    let usage = snapshot.utilization().unwrap_or(0.0);
+    let mem_pressure = snapshot.memory_pressure().unwrap_or(0.0);
+    let (network_rx, network_tx) = snapshot.network.unwrap_or((0, 0));
+    let (disk_read, disk_write) = snapshot.disk.unwrap_or((0, 0));
 
-    if temp > config.hardware.thresholds.nvidia_temp {
+    if overheating_sensor(snapshot, &config.hardware.thresholds).is_some() {
         return SpriteState::Overheating;
     }
 
@@ -93,8 +147,13 @@ pub fn determine_state(
         return SpriteState::Sleeping;
     }
 
-    if bat_lvl > 0.0 && bat_lvl < config.battery.low_battery_threshold && _bat_state != "Charging" {
-        return SpriteState::LowBattery;
+    if let Some((bat_lvl, bat_state)) = &snapshot.battery {
+        if *bat_lvl > 0.0
+            && *bat_lvl < config.battery.low_battery_threshold
+            && bat_state != "Charging"
+        {
+            return SpriteState::LowBattery;
+        }
     }
 
     if usage > 80.0 || mem_pressure > 90.0 {
@@ -3,38 +3,27 @@ mod tests {
 
     use crate::config::AppConfig;
     use crate::monitors::{
-        state::{determine_mood, determine_state, is_within_sleep_time, Mood, SpriteState},
+        state::{
+            determine_mood, determine_state, is_within_sleep_time, overheating_sensor, Mood,
+            MoodOverride, SpriteState,
+        },
         window::AppCategory,
-        HardwareMonitor,
+        MetricsSnapshot, Sensor, TemperatureReading,
     };
     use chrono::NaiveTime;
 
-    struct MockMonitor {
-        temp: f32,
-        util: f32,
-    }
-
-    impl HardwareMonitor for MockMonitor {
-        fn get_temperature(&self) -> f32 {
-            self.temp
-        }
-        fn get_utilization(&self) -> f32 {
-            self.util
-        }
-        fn get_memory_usage(&self) -> (u64, u64) {
-            (0, 0)
-        }
-        fn get_network_usage(&self) -> (u64, u64) {
-            (0, 0)
-        }
-        fn get_disk_usage(&self) -> (u64, u64) {
-            (0, 0)
-        }
-        fn get_battery_status(&self) -> (f32, String) {
-            (100.0, "Full".to_string())
-        }
-        fn is_available(&self) -> bool {
-            true
+    fn snapshot(temp: f32, util: f32) -> MetricsSnapshot {
+        MetricsSnapshot {
+            temperatures: vec![TemperatureReading {
+                sensor: Sensor::NvidiaGpu,
+                celsius: temp,
+            }],
+            cpu_utilization: Some(util),
+            memory: Some((0, 0)),
+            network: Some((0, 0)),
+            disk: Some((0, 0)),
+            battery: Some((100.0, "Full".to_string())),
+            ..MetricsSnapshot::now()
         }
     }
 
@@ -46,117 +35,142 @@ mod tests {
 
     #[test]
     fn test_overheating_priority() {
-        let monitor = MockMonitor {
-            temp: 90.0,
-            util: 10.0,
-        };
+        let snapshot = snapshot(90.0, 10.0);
         let config = create_config(80.0);
-        let state = determine_state(&monitor, 0, 0, 0, 0, AppCategory::Idle, &config);
+        let state = determine_state(&snapshot, AppCategory::Idle, &config);
         assert_eq!(state, SpriteState::Overheating);
     }
 
+    #[test]
+    fn test_each_sensor_uses_its_own_threshold() {
+        let config = AppConfig::default();
+        let reading = |sensor, celsius| TemperatureReading { sensor, celsius };
+        let mut snapshot = snapshot(60.0, 10.0);
+
+        // 82°C is over the GPU threshold (80) but not the CPU one (85).
+        snapshot.temperatures = vec![reading(Sensor::Cpu, 82.0), reading(Sensor::AmdGpu, 70.0)];
+        assert_eq!(
+            determine_state(&snapshot, AppCategory::Idle, &config),
+            SpriteState::Idle
+        );
+
+        snapshot.temperatures = vec![reading(Sensor::Cpu, 70.0), reading(Sensor::IntelGpu, 82.0)];
+        assert_eq!(
+            determine_state(&snapshot, AppCategory::Idle, &config),
+            SpriteState::Overheating
+        );
+        assert_eq!(
+            overheating_sensor(&snapshot, &config.hardware.thresholds),
+            Some(Sensor::IntelGpu)
+        );
+
+        // The sensor furthest over its threshold is reported.
+        snapshot.temperatures = vec![
+            reading(Sensor::Cpu, 90.0),
+            reading(Sensor::NvidiaGpu, 81.0),
+            reading(Sensor::Battery, 58.0),
+        ];
+        assert_eq!(
+            overheating_sensor(&snapshot, &config.hardware.thresholds),
+            Some(Sensor::Battery)
+        );
+    }
+
     #[test]
     fn test_high_load_priority() {
-        let monitor = MockMonitor {
-            temp: 60.0,
-            util: 90.0,
-        };
+        let snapshot = snapshot(60.0, 90.0);
         let config = create_config(80.0);
-        let state = determine_state(&monitor, 0, 0, 0, 0, AppCategory::Idle, &config);
+        let state = determine_state(&snapshot, AppCategory::Idle, &config);
         assert_eq!(state, SpriteState::HighLoad);
     }
 
     #[test]
     fn test_memory_pressure_high_load() {
-        struct MemoryMonitor {
-            used: u64,
-            total: u64,
-        }
-        impl HardwareMonitor for MemoryMonitor {
-            fn get_temperature(&self) -> f32 {
-                40.0
-            }
-            fn get_utilization(&self) -> f32 {
-                10.0
-            }
-            fn get_memory_usage(&self) -> (u64, u64) {
-                (self.used, self.total)
-            }
-            fn get_network_usage(&self) -> (u64, u64) {
-                (0, 0)
-            }
-            fn get_disk_usage(&self) -> (u64, u64) {
-                (0, 0)
-            }
-            fn get_battery_status(&self) -> (f32, String) {
-                (100.0, "Full".to_string())
-            }
-            fn is_available(&self) -> bool {
-                true
-            }
-        }
+        let snapshot = MetricsSnapshot {
+            memory: Some((950, 1000)),
+            ..snapshot(40.0, 10.0)
+        };
+        let config = create_config(80.0);
+        let state = determine_state(&snapshot, AppCategory::Idle, &config);
+        assert_eq!(state, SpriteState::HighLoad);
+    }
 
-        let monitor = MemoryMonitor {
-            used: 950,
-            total: 1000,
+    #[test]
+    fn test_vram_pressure_counts_only_without_ram() {
+        let gpu_only = MetricsSnapshot {
+            memory: None,
+            vram: Some((950, 1000)),
+            ..snapshot(40.0, 10.0)
         };
         let config = create_config(80.0);
-        let state = determine_state(&monitor, 0, 0, 0, 0, AppCategory::Idle, &config);
+        let state = determine_state(&gpu_only, AppCategory::Idle, &config);
         assert_eq!(state, SpriteState::HighLoad);
+
+        let composite = MetricsSnapshot {
+            memory: Some((100, 1000)),
+            ..gpu_only
+        };
+        assert_eq!(composite.memory_pressure(), Some(10.0));
     }
 
     #[test]
     fn test_high_network_load() {
-        let monitor = MockMonitor {
-            temp: 60.0,
-            util: 10.0,
-        };
+        let snapshot = snapshot(60.0, 10.0);
         let config = create_config(80.0);
-        let state = determine_state(&monitor, 3000, 0, 0, 0, AppCategory::Idle, &config);
+        let state = determine_state(
+            &MetricsSnapshot {
+                network: Some((3000, 0)),
+                ..snapshot
+            },
+            AppCategory::Idle,
+            &config,
+        );
         assert_eq!(state, SpriteState::HighLoad);
     }
 
     #[test]
     fn test_high_disk_load() {
-        let monitor = MockMonitor {
-            temp: 60.0,
-            util: 10.0,
-        };
+        let snapshot = snapshot(60.0, 10.0);
         let config = create_config(80.0);
-        let state = determine_state(&monitor, 0, 0, 20000, 0, AppCategory::Idle, &config);
+        let state = determine_state(
+            &MetricsSnapshot {
+                disk: Some((20000, 0)),
+                ..snapshot
+            },
+            AppCategory::Idle,
+            &config,
+        );
         assert_eq!(state, SpriteState::HighLoad);
     }
 
     #[test]
     fn test_coding_activity() {
-        let monitor = MockMonitor {
-            temp: 60.0,
-            util: 10.0,
-        };
+        let snapshot = snapshot(60.0, 10.0);
         let config = create_config(80.0);
-        let state = determine_state(&monitor, 0, 0, 0, 0, AppCategory::Coding, &config);
+        let state = determine_state(&snapshot, AppCategory::Coding, &config);
         assert_eq!(state, SpriteState::Working);
     }
 
     #[test]
     fn test_gaming_activity() {
-        let monitor = MockMonitor {
-            temp: 60.0,
-            util: 10.0,
-        };
+        let snapshot = snapshot(60.0, 10.0);
         let config = create_config(80.0);
-        let state = determine_state(&monitor, 0, 0, 0, 0, AppCategory::Gaming, &config);
+        let state = determine_state(&snapshot, AppCategory::Gaming, &config);
         assert_eq!(state, SpriteState::Gaming);
     }
 
     #[test]
     fn test_idle_default() {
-        let monitor = MockMonitor {
-            temp: 60.0,
-            util: 10.0,
-        };
+        let snapshot = snapshot(60.0, 10.0);
         let config = create_config(80.0);
-        let state = determine_state(&monitor, 0, 0, 0, 0, AppCategory::Unknown, &config);
+        let state = determine_state(&snapshot, AppCategory::Unknown, &config);
+        assert_eq!(state, SpriteState::Idle);
+    }
+
+    #[test]
+    fn test_missing_sensors_do_not_trigger_states() {
+        let config = create_config(80.0);
+        let state = determine_state(&MetricsSnapshot::now(), AppCategory::Unknown, &config);
         assert_eq!(state, SpriteState::Idle);
     }
 
@@ -241,4 +255,26 @@ mod tests {
             NaiveTime::from_hms_opt(6, 0, 0).unwrap()
         ));
     }
+
+    #[test]
+    fn test_mood_from_name() {
+        assert_eq!(Mood::from_name("happy"), Some(Mood::Happy));
+        assert_eq!(Mood::from_name(" Sleeping "), Some(Mood::Sleeping));
+        assert_eq!(Mood::from_name("grumpy"), None);
+    }
+
+    #[test]
+    fn test_mood_override_expires() {
+        let now = std::time::Instant::now();
+        let slot = MoodOverride::default();
+        assert_eq!(slot.current(now), None);
+
+        slot.set(Mood::Excited, std::time::Duration::from_secs(60), now);
+        assert_eq!(
+            slot.current(now + std::time::Duration::from_secs(59)),
+            Some(Mood::Excited)
+        );
+        assert_eq!(slot.current(now + std::time::Duration::from_secs(60)), None);
+        assert_eq!(slot.current(now), None);
+    }
 }
@@ -0,0 +1,211 @@
+use crate::monitors::{HardwareMonitor, MetricsSnapshot, Sensor, TemperatureReading};
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+#[path = "sysfs_gpu_test.rs"]
+mod sysfs_gpu_test;
+
+/// Kernel drivers whose sysfs layout we understand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuDriver {
+    Amdgpu,
+    I915,
+    Xe,
+}
+
+impl GpuDriver {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "amdgpu" => Some(Self::Amdgpu),
+            "i915" => Some(Self::I915),
+            "xe" => Some(Self::Xe),
+            _ => None,
+        }
+    }
+
+    fn sensor(self) -> Sensor {
+        match self {
+            Self::Amdgpu => Sensor::AmdGpu,
+            Self::I915 | Self::Xe => Sensor::IntelGpu,
+        }
+    }
+}
+
+/// A GPU found under `class/drm`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuCard {
+    /// `class/drm/cardN`.
+    pub card: PathBuf,
+    /// `class/drm/cardN/device`.
+    pub device: PathBuf,
+    pub driver: GpuDriver,
+}
+
+impl GpuCard {
+    /// Dedicated VRAM in bytes; integrated GPUs have none.
+    fn vram_total(&self) -> Option<u64> {
+        read_number(&self.device.join("mem_info_vram_total"))
+    }
+}
+
+/// Reads AMD (amdgpu) and Intel (i915, xe) GPUs from Linux sysfs.
+///
+/// Only GPU figures are reported; network, disk and battery are `None`.
+pub struct SysfsGpuMonitor {
+    card: GpuCard,
+}
+
+impl SysfsGpuMonitor {
+    /// Monitor the preferred GPU under `/sys`, if there is a supported one.
+    pub fn new() -> Option<Self> {
+        Self::with_root("/sys")
+    }
+
+    /// Monitor the preferred GPU under a sysfs tree mounted at `root`.
+    pub fn with_root(root: impl AsRef<Path>) -> Option<Self> {
+        let card = detect(root.as_ref()).into_iter().next()?;
+        tracing::info!(
+            "Monitoring {:?} GPU at {}",
+            card.driver,
+            card.device.display()
+        );
+        Some(Self { card })
+    }
+
+    pub fn card(&self) -> &GpuCard {
+        &self.card
+    }
+
+    /// hwmon directory of the device, e.g. `device/hwmon/hwmon3`.
+    fn hwmon(&self) -> Option<PathBuf> {
+        let mut dirs: Vec<PathBuf> = std::fs::read_dir(self.card.device.join("hwmon"))
+            .ok()?
+            .flatten()
+            .map(|e| e.path())
+            .collect();
+        dirs.sort();
+        dirs.into_iter().next()
+    }
+
+    /// Busy percentage from the `gpu_busy_percent` counter, or for Intel
+    /// GPUs without it, estimated from the current vs. maximum frequency.
+    fn busy_percent(&self) -> Option<f32> {
+        if let Some(busy) = read_number(&self.card.device.join("gpu_busy_percent")) {
+            return Some(busy as f32);
+        }
+        let (current, max) = match self.card.driver {
+            GpuDriver::Amdgpu => return None,
+            GpuDriver::I915 => (
+                self.card.card.join("gt_act_freq_mhz"),
+                self.card.card.join("gt_RP0_freq_mhz"),
+            ),
+            GpuDriver::Xe => {
+                let freq = self.card.device.join("tile0/gt0/freq0");
+                (freq.join("act_freq"), freq.join("rp0_freq"))
+            }
+        };
+        let (current, max) = (read_number(&current)?, read_number(&max)?);
+        (max > 0).then(|| (current as f32 / max as f32 * 100.0).min(100.0))
+    }
+}
+
+/// Supported GPUs under `root/class/drm`, those with dedicated VRAM first,
+/// then by card number.
+pub fn detect(root: &Path) -> Vec<GpuCard> {
+    let Ok(entries) = std::fs::read_dir(root.join("class/drm")) else {
+        return Vec::new();
+    };
+    let mut cards: Vec<(u32, GpuCard)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Skip connectors such as `card0-DP-1`.
+            let index: u32 = name.strip_prefix("card")?.parse().ok()?;
+            let card = entry.path();
+            let device = card.join("device");
+            let driver = driver_name(&device).and_then(|d| GpuDriver::from_name(&d))?;
+            Some((
+                index,
+                GpuCard {
+                    card,
+                    device,
+                    driver,
+                },
+            ))
+        })
+        .collect();
+    cards.sort_by_key(|(index, card)| (card.vram_total().is_none(), *index));
+    cards.into_iter().map(|(_, card)| card).collect()
+}
+
+/// `DRIVER=` from the device's `uevent`.
+fn driver_name(device: &Path) -> Option<String> {
+    std::fs::read_to_string(device.join("uevent"))
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("DRIVER="))
+        .map(|driver| driver.trim().to_string())
+}
+
+fn read_number(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Temperature in °C from an hwmon directory: the `edge` sensor if one is
+/// labelled so, otherwise the lowest-numbered `temp*_input`.
+fn hwmon_temperature(hwmon: &Path) -> Option<f32> {
+    let mut inputs: Vec<(u32, PathBuf)> = std::fs::read_dir(hwmon)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let index = name
+                .strip_prefix("temp")?
+                .strip_suffix("_input")?
+                .parse()
+                .ok()?;
+            Some((index, entry.path()))
+        })
+        .collect();
+    inputs.sort();
+
+    let label = |index: u32| {
+        std::fs::read_to_string(hwmon.join(format!("temp{}_label", index)))
+            .map(|l| l.trim().to_lowercase())
+            .unwrap_or_default()
+    };
+    let (_, input) = inputs
+        .iter()
+        .find(|(index, _)| label(*index) == "edge")
+        .or_else(|| inputs.first())?;
+    // Millidegrees Celsius.
+    read_number(input).map(|millis| millis as f32 / 1000.0)
+}
+
+impl HardwareMonitor for SysfsGpuMonitor {
+    fn sample(&self) -> MetricsSnapshot {
+        let used = read_number(&self.card.device.join("mem_info_vram_used"));
+        let vram = match (used, self.card.vram_total()) {
+            (Some(used), Some(total)) => Some((used / 1024 / 1024, total / 1024 / 1024)),
+            _ => None,
+        };
+        MetricsSnapshot {
+            temperatures: self
+                .hwmon()
+                .and_then(|hwmon| hwmon_temperature(&hwmon))
+                .map(|celsius| TemperatureReading {
+                    sensor: self.card.driver.sensor(),
+                    celsius,
+                })
+                .into_iter()
+                .collect(),
+            gpu_utilization: self.busy_percent(),
+            vram,
+            ..MetricsSnapshot::now()
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        self.card.device.exists()
+    }
+}
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use crate::monitors::sysfs_gpu::{detect, GpuDriver, SysfsGpuMonitor};
+    use crate::monitors::{HardwareMonitor, Sensor};
+    use std::path::{Path, PathBuf};
+
+    /// A throwaway directory laid out like `/sys`.
+    struct FakeSysfs {
+        root: PathBuf,
+    }
+
+    impl FakeSysfs {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "ethereal-sysfs-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&root);
+            std::fs::create_dir_all(root.join("class/drm")).unwrap();
+            Self { root }
+        }
+
+        /// Write `files` (relative path, contents) under `class/drm/<card>`.
+        fn card(&self, card: &str, files: &[(&str, &str)]) -> &Self {
+            for (path, contents) in files {
+                self.write(&Path::new("class/drm").join(card).join(path), contents);
+            }
+            self
+        }
+
+        fn write(&self, path: &Path, contents: &str) {
+            let path = self.root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+    }
+
+    impl Drop for FakeSysfs {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn amd_card(sysfs: &FakeSysfs, card: &str) {
+        sysfs.card(
+            card,
+            &[
+                ("device/uevent", "DRIVER=amdgpu\nPCI_ID=1002:73BF\n"),
+                ("device/gpu_busy_percent", "37\n"),
+                ("device/mem_info_vram_used", "2147483648\n"),
+                ("device/mem_info_vram_total", "8589934592\n"),
+                ("device/hwmon/hwmon4/temp1_input", "54000\n"),
+                ("device/hwmon/hwmon4/temp1_label", "edge\n"),
+                ("device/hwmon/hwmon4/temp2_input", "61000\n"),
+                ("device/hwmon/hwmon4/temp2_label", "junction\n"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_reads_amdgpu() {
+        let sysfs = FakeSysfs::new("amd");
+        amd_card(&sysfs, "card0");
+        sysfs.card("card0-DP-1", &[("status", "connected")]);
+
+        let monitor = SysfsGpuMonitor::with_root(&sysfs.root).unwrap();
+        assert_eq!(monitor.card().driver, GpuDriver::Amdgpu);
+        assert!(monitor.is_available());
+        let sample = monitor.sample();
+        assert_eq!(sample.gpu_utilization, Some(37.0));
+        assert_eq!(sample.vram, Some((2048, 8192)));
+        assert_eq!(sample.temperature(), Some(54.0));
+        assert_eq!(sample.temperatures[0].sensor, Sensor::AmdGpu);
+        assert_eq!(sample.battery, None);
+    }
+
+    #[test]
+    fn test_unlabelled_sensor_falls_back_to_first_input() {
+        let sysfs = FakeSysfs::new("unlabelled");
+        sysfs.card(
+            "card0",
+            &[
+                ("device/uevent", "DRIVER=amdgpu\n"),
+                ("device/hwmon/hwmon1/temp2_input", "70000\n"),
+                ("device/hwmon/hwmon1/temp1_input", "48500\n"),
+            ],
+        );
+        let sample = SysfsGpuMonitor::with_root(&sysfs.root).unwrap().sample();
+        assert_eq!(sample.temperature(), Some(48.5));
+        // No counters: absent rather than errors.
+        assert_eq!(sample.gpu_utilization, None);
+        assert_eq!(sample.vram, None);
+    }
+
+    #[test]
+    fn test_i915_busy_from_frequency() {
+        let sysfs = FakeSysfs::new("i915");
+        sysfs.card(
+            "card1",
+            &[
+                ("device/uevent", "DRIVER=i915\n"),
+                ("gt_act_freq_mhz", "650\n"),
+                ("gt_RP0_freq_mhz", "1300\n"),
+            ],
+        );
+        let monitor = SysfsGpuMonitor::with_root(&sysfs.root).unwrap();
+        assert_eq!(monitor.card().driver, GpuDriver::I915);
+        let sample = monitor.sample();
+        assert_eq!(sample.gpu_utilization, Some(50.0));
+        assert_eq!(sample.temperature(), None);
+    }
+
+    #[test]
+    fn test_xe_busy_from_frequency() {
+        let sysfs = FakeSysfs::new("xe");
+        sysfs.card(
+            "card0",
+            &[
+                ("device/uevent", "DRIVER=xe\n"),
+                ("device/tile0/gt0/freq0/act_freq", "2400\n"),
+                ("device/tile0/gt0/freq0/rp0_freq", "2000\n"),
+            ],
+        );
+        let monitor = SysfsGpuMonitor::with_root(&sysfs.root).unwrap();
+        assert_eq!(monitor.sample().gpu_utilization, Some(100.0));
+    }
+
+    #[test]
+    fn test_detect_prefers_dedicated_gpu() {
+        let sysfs = FakeSysfs::new("hybrid");
+        sysfs.card("card0", &[("device/uevent", "DRIVER=i915\n")]);
+        amd_card(&sysfs, "card1");
+        sysfs.card("card2", &[("device/uevent", "DRIVER=nouveau\n")]);
+
+        let cards = detect(&sysfs.root);
+        assert_eq!(
+            cards.iter().map(|c| c.driver).collect::<Vec<_>>(),
+            vec![GpuDriver::Amdgpu, GpuDriver::I915]
+        );
+        assert!(cards[0].card.ends_with("card1"));
+    }
+
+    #[test]
+    fn test_no_supported_gpu() {
+        let sysfs = FakeSysfs::new("none");
+        assert!(SysfsGpuMonitor::with_root(&sysfs.root).is_none());
+        assert!(SysfsGpuMonitor::with_root(sysfs.root.join("missing")).is_none());
+    }
+}
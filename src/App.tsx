@@ -6,10 +6,19 @@ import { StateOverlay } from '@components/StateOverlay';
 import { WelcomeModal } from '@components/WelcomeModal';
 import { useDraggable } from '@hooks/useDraggable';
 import { useWindowPosition } from '@hooks/useWindowPosition';
+import { aiErrorReply } from '@lib/aiError';
+import { askEthereal } from '@lib/chat';
 import { logger } from '@lib/logger';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { AnimatePresence, motion, useMotionValue, useSpring } from 'framer-motion';
+import {
+  AnimatePresence,
+  type TargetAndTransition,
+  motion,
+  useAnimationControls,
+  useMotionValue,
+  useSpring,
+} from 'framer-motion';
 import { useEffect } from 'react';
 import './App.css';
 import { useSoundEffects } from './hooks/useSoundEffects';
@@ -17,12 +26,49 @@ import { useChatStore } from './stores/chatStore';
 import { useSettingsStore } from './stores/settingsStore';
 import { useSoundStore } from './stores/soundStore';
 import { type HardwareData, useSpriteStore } from './stores/spriteStore';
+import type { ClipboardOffer } from './types/config';
+
+/** What the sprite says when offering clipboard actions. */
+const offerLine = (offer: ClipboardOffer): string => {
+  switch (offer.kind) {
+    case 'stack_trace':
+      return 'Ouch, a stack trace. Want me to explain it?';
+    case 'compiler_error':
+      return 'The compiler is upset. Want me to explain?';
+    case 'code':
+      return 'Some code! Want me to walk through it?';
+    default:
+      return offer.language && offer.actions.includes('translate')
+        ? `That looks like ${offer.language}. Want a translation?`
+        : 'That is a lot of text. Want the short version?';
+  }
+};
+
+/** Payload of the backend's unprompted `sprite-says` event. */
+interface SpriteSays {
+  text: string;
+  trigger: { kind: 'entered' | 'finished_work' | 'woke_up'; state?: string; minutes?: number };
+}
+
+/** Keyframes for the backend's `sprite-emote` event, by emote name. */
+const EMOTES: Record<string, TargetAndTransition> = {
+  wave: { rotate: [0, -12, 12, -12, 12, 0], transition: { duration: 1 } },
+  bounce: { y: [0, -30, 0, -15, 0], transition: { duration: 0.8 } },
+  spin: { rotate: [0, 360], transition: { duration: 0.8 } },
+  nod: { y: [0, 8, 0, 8, 0], transition: { duration: 0.8 } },
+  shake: { x: [0, -10, 10, -10, 10, 0], transition: { duration: 0.6 } },
+  sparkle: {
+    scale: [1, 1.15, 1],
+    filter: ['brightness(1)', 'brightness(1.8)', 'brightness(1)'],
+    transition: { duration: 1.2 },
+  },
+};
 
 function App() {
   const { startDragging } = useDraggable();
   useWindowPosition();
   useSoundEffects();
-  const { setThinking, showResponse, setVisible, addToHistory, history } = useChatStore();
+  const { showResponse, showOffer, addToHistory, history } = useChatStore();
   const { initialize: initSettings, config, setIsOpen, updateConfig } = useSettingsStore();
   const {
     updateHardware,
@@ -36,6 +82,7 @@ function App() {
     setCustomSpritePath,
   } = useSpriteStore();
   const { syncWithConfig } = useSoundStore();
+  const emoteControls = useAnimationControls();
 
   // Parallax motion values
   const mouseX = useMotionValue(0);
@@ -55,6 +102,12 @@ function App() {
     };
     document.addEventListener('contextmenu', handleContextMenu);
 
+    // Escape dismisses the bubble and aborts the reply it was waiting for.
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (e.key === 'Escape') useChatStore.getState().dismiss();
+    };
+    document.addEventListener('keydown', handleKeyDown);
+
     const unlisteners: (() => void)[] = [];
 
     const setupListeners = async () => {
@@ -82,14 +135,10 @@ function App() {
         });
         unlisteners.push(unlistenShortcut);
 
-        const unlistenClipboard = await listen<string>('clipboard-changed', async (event) => {
-          const content = event.payload;
+        // Talk about the copied text, as before clipboard actions existed.
+        const chatAboutClip = async (content: string) => {
           const { state, mood, hardware: hw } = useSpriteStore.getState();
-          const { history: chatHistory } = useChatStore.getState();
 
-          logger.info('Clipboard changed detected');
-          setThinking(true);
-          setVisible(true);
           addToHistory('user', content);
 
           try {
@@ -99,20 +148,51 @@ function App() {
               hw?.network_rx
             }KB/s down, Bat: ${hw?.battery_level}% (${hw?.battery_state})`;
 
-            const response = await invoke<string>('chat_with_ethereal', {
+            const response = await askEthereal({
               message: content,
-              history: chatHistory,
               systemContext: system_context,
               mood: mood,
+              source: 'clipboard',
             });
-            showResponse(response);
+            if (response !== null) showResponse(response);
           } catch (e) {
             logger.error('AI Chat failed:', e);
-            showResponse("I'm having trouble connecting to my brain...");
+            showResponse(aiErrorReply(e, "I'm having trouble connecting to my brain..."));
           }
+        };
+
+        const unlistenClipboard = await listen<string>('clipboard-changed', async (event) => {
+          logger.info('Clipboard changed detected');
+          // With actions on, the backend follows up with `clipboard-offer`.
+          if (useSettingsStore.getState().config?.clipboard?.actions_enabled ?? true) return;
+          await chatAboutClip(event.payload);
         });
         unlisteners.push(unlistenClipboard);
 
+        const unlistenOffer = await listen<ClipboardOffer>('clipboard-offer', async (event) => {
+          const offer = event.payload;
+          if (offer.actions.length === 0) {
+            await chatAboutClip(offer.text);
+            return;
+          }
+          showOffer(offerLine(offer), offer);
+        });
+        unlisteners.push(unlistenOffer);
+
+        const unlistenSpriteSays = await listen<SpriteSays>('sprite-says', (event) => {
+          // Don't talk over a reply the user is waiting for.
+          const { isThinking, isStreaming } = useChatStore.getState();
+          if (isThinking || isStreaming) return;
+          showResponse(event.payload.text);
+        });
+        unlisteners.push(unlistenSpriteSays);
+
+        const unlistenEmote = await listen<{ emote: string }>('sprite-emote', (event) => {
+          const emote = EMOTES[event.payload.emote];
+          if (emote) emoteControls.start(emote);
+        });
+        unlisteners.push(unlistenEmote);
+
         const unlistenHardware = await listen<HardwareData>('gpu-update', (event) => {
           updateHardware(event.payload);
         });
@@ -152,6 +232,7 @@ function App() {
 
     return () => {
       document.removeEventListener('contextmenu', handleContextMenu);
+      document.removeEventListener('keydown', handleKeyDown);
       for (const unlisten of unlisteners) {
         unlisten();
       }
@@ -160,13 +241,13 @@ function App() {
     initSettings,
     setIsOpen,
     toggleClickThrough,
-    setThinking,
-    setVisible,
     showResponse,
+    showOffer,
     updateHardware,
     addToHistory,
     config,
     updateConfig,
+    emoteControls,
   ]);
 
   // Initial greeting on mount
@@ -180,16 +261,12 @@ function App() {
         const time = new Date().toLocaleTimeString();
         const system_context = `Current State: ${spriteState}, Mood: ${spriteMood}, Current Local Time: ${time}, [GREETING MODE: Say hello to the user based on the time of day]`;
 
-        setThinking(true);
-        setVisible(true);
-
-        const response = await invoke<string>('chat_with_ethereal', {
+        const response = await askEthereal({
           message: 'system_init_greeting',
-          history: [],
           systemContext: system_context,
           mood: spriteMood,
         });
-        showResponse(response);
+        if (response !== null) showResponse(response);
       } catch (e) {
         logger.debug('Auto-greeting failed:', e);
       }
@@ -197,22 +274,24 @@ function App() {
 
     const timer = setTimeout(sendGreeting, 2000);
     return () => clearTimeout(timer);
-  }, [config, spriteState, spriteMood, setThinking, setVisible, showResponse, history.length]);
+  }, [config, spriteState, spriteMood, showResponse, history.length]);
 
   useEffect(() => {
     if (config?.sound) {
       syncWithConfig(config.sound);
     }
-    if (config?.interaction?.custom_sprite_path) {
-      setCustomSpritePath(config.interaction.custom_sprite_path);
-    }
+    if (!config) return;
+    const persona = config.ai?.active_persona
+      ? config.ai.personas?.[config.ai.active_persona]
+      : undefined;
+    // Always set it, so leaving a persona with its own sprite restores the default.
+    const spritePath = persona?.sprite_path || config.interaction?.custom_sprite_path;
+    setCustomSpritePath(spritePath || null);
   }, [config, syncWithConfig, setCustomSpritePath]);
 
   const handleDoubleClick = async () => {
     if (config?.interaction?.double_click_action === 'chat') {
       const message = 'Hello! I just double-clicked you.';
-      setThinking(true);
-      setVisible(true);
       addToHistory('user', message);
       try {
         const time = new Date().toLocaleTimeString();
@@ -222,16 +301,15 @@ function App() {
           hardware?.network_rx
         }KB/s down, Bat: ${hardware?.battery_level}% (${hardware?.battery_state})`;
 
-        const response = await invoke<string>('chat_with_ethereal', {
+        const response = await askEthereal({
           message: message,
-          history: history,
           systemContext: system_context,
           mood: spriteMood,
         });
-        showResponse(response);
+        if (response !== null) showResponse(response);
       } catch (e) {
         logger.error('AI Chat failed on double click:', e);
-        showResponse('You tickle! But my brain is currently offline.');
+        showResponse(aiErrorReply(e, 'You tickle! But my brain is currently offline.'));
       }
     } else if (config?.interaction?.double_click_action === 'settings') {
       setIsOpen(true);
@@ -386,13 +464,15 @@ function App() {
             />
           </div>
 
-          <SpriteAnimator
-            frames={getAnimationFrames()}
-            fps={getCurrentFps()}
-            loop={shouldLoop()}
-            mood={spriteMood}
-            className="w-full h-full animate-spirit-float"
-          />
+          <motion.div animate={emoteControls} className="w-full h-full">
+            <SpriteAnimator
+              frames={getAnimationFrames()}
+              fps={getCurrentFps()}
+              loop={shouldLoop()}
+              mood={spriteMood}
+              className="w-full h-full animate-spirit-float"
+            />
+          </motion.div>
 
           {getAnimationFrames().length === 0 && (
             <div className="absolute inset-0 glass-effect rounded-full animate-pulse flex items-center justify-center">
@@ -43,7 +43,8 @@ describe('Character Interaction Integration', () => {
     hardware: {
       monitor_source: 'auto',
       polling_interval_ms: 2000,
-      thresholds: { nvidia_temp: 80, amd_temp: 80, cpu_temp: 85 },
+      thresholds: { nvidia_temp: 80, amd_temp: 80, cpu_temp: 85, battery_temp: 50 },
+      cpu_sensor_labels: ['cpu', 'package', 'core'],
     },
     ai: {
       model_name: 'llama3.2',
@@ -69,7 +70,7 @@ describe('Character Interaction Integration', () => {
 
     (invoke as Mock).mockImplementation(async (cmd: string) => {
       if (cmd === 'get_config') return mockConfig;
-      if (cmd === 'chat_with_ethereal') return 'Ouch! Stop double clicking me!';
+      if (cmd === 'chat_with_ethereal_stream') return 'Ouch! Stop double clicking me!';
       return null;
     });
 
@@ -53,7 +53,8 @@ describe('Chat Flow Integration', () => {
     hardware: {
       monitor_source: 'auto',
       polling_interval_ms: 2000,
-      thresholds: { nvidia_temp: 80, amd_temp: 80, cpu_temp: 85 },
+      thresholds: { nvidia_temp: 80, amd_temp: 80, cpu_temp: 85, battery_temp: 50 },
+      cpu_sensor_labels: ['cpu', 'package', 'core'],
     },
     ai: {
       model_name: 'llama3.2',
@@ -71,6 +72,8 @@ describe('Chat Flow Integration', () => {
     battery: { low_battery_threshold: 20.0, notify_on_low_battery: true },
     autostart: { enabled: false },
     privacy: { share_window_title: false },
+    // Plain chat about copied text; offers are covered by the backend tests.
+    clipboard: { actions_enabled: false },
   };
 
   beforeEach(() => {
@@ -79,7 +82,7 @@ describe('Chat Flow Integration', () => {
 
     (invoke as Mock).mockImplementation(async (cmd) => {
       if (cmd === 'get_config') return mockConfig;
-      if (cmd === 'chat_with_ethereal') return 'Hello from AI!';
+      if (cmd === 'chat_with_ethereal_stream') return 'Hello from AI!';
       return null;
     });
 
@@ -122,10 +125,9 @@ describe('Chat Flow Integration', () => {
     });
 
     expect(invoke).toHaveBeenCalledWith(
-      'chat_with_ethereal',
+      'chat_with_ethereal_stream',
       expect.objectContaining({
         message: 'User copied text',
-        history: [],
       }),
     );
 
@@ -169,15 +171,14 @@ describe('Chat Flow Integration', () => {
     });
 
     expect(invoke).toHaveBeenCalledWith(
-      'chat_with_ethereal',
+      'chat_with_ethereal_stream',
       expect.objectContaining({
         message: 'Second message',
-        history: expect.arrayContaining([
-          { role: 'user', content: 'First message' },
-          { role: 'assistant', content: 'First response' },
-        ]),
       }),
     );
+    // The backend replays the stored conversation itself.
+    const chatCall = (invoke as Mock).mock.calls.find(([cmd]) => cmd === 'chat_with_ethereal_stream');
+    expect(chatCall?.[1]).not.toHaveProperty('history');
 
     await waitFor(() => {
       const state = useChatStore.getState();
@@ -214,10 +215,9 @@ describe('Chat Flow Integration', () => {
     });
 
     expect(invoke).toHaveBeenCalledWith(
-      'chat_with_ethereal',
+      'chat_with_ethereal_stream',
       expect.objectContaining({
         message: 'User copied text',
-        history: [],
       }),
     );
 
@@ -228,4 +228,118 @@ describe('Chat Flow Integration', () => {
       expect(state.isThinking).toBe(false);
     });
   });
+
+  it('types streamed tokens into the bubble as they arrive', async () => {
+    let clipboardCallback: ((event: any) => void) | undefined;
+    let tokenCallback: ((event: any) => void) | undefined;
+    const partials: (string | null)[] = [];
+
+    (listen as Mock).mockImplementation(async (event, callback) => {
+      if (event === 'clipboard-changed') clipboardCallback = callback;
+      if (event === 'chat-token') tokenCallback = callback;
+      return () => {};
+    });
+
+    (invoke as Mock).mockImplementation(async (cmd, args) => {
+      if (cmd !== 'chat_with_ethereal_stream') return null;
+      const request_id = args.requestId;
+      tokenCallback?.({ payload: { request_id, text: 'Hello' } });
+      partials.push(useChatStore.getState().message);
+      // Left over from an earlier, cancelled request.
+      tokenCallback?.({ payload: { request_id: 'chat-stale', text: ' STALE' } });
+      tokenCallback?.({ payload: { request_id, text: ', mortal.' } });
+      partials.push(useChatStore.getState().message);
+      return 'Hello, mortal.';
+    });
+
+    render(<App />);
+    await waitFor(() => expect(clipboardCallback).toBeDefined());
+
+    await act(async () => {
+      await clipboardCallback?.({ payload: 'Say hi' });
+    });
+
+    expect(partials).toEqual(['Hello', 'Hello, mortal.']);
+    const state = useChatStore.getState();
+    expect(state.message).toBe('Hello, mortal.');
+    expect(state.isStreaming).toBe(false);
+    expect(state.history[state.history.length - 1]).toEqual({
+      role: 'assistant',
+      content: 'Hello, mortal.',
+    });
+  });
+
+  it('uses the non-streaming command for structured replies', async () => {
+    let clipboardCallback: ((event: any) => void) | undefined;
+
+    (listen as Mock).mockImplementation(async (event, callback) => {
+      if (event === 'clipboard-changed') clipboardCallback = callback;
+      return () => {};
+    });
+    (invoke as Mock).mockResolvedValue('Waves.');
+    useSettingsStore.setState({
+      config: {
+        ...mockConfig,
+        actions: { enabled: true, allowed: ['emote'], mood_minutes: 10 },
+      } as any,
+    });
+
+    render(<App />);
+    await waitFor(() => expect(clipboardCallback).toBeDefined());
+
+    await act(async () => {
+      await clipboardCallback?.({ payload: 'Wave at me' });
+    });
+
+    expect(invoke).toHaveBeenCalledWith(
+      'chat_with_ethereal',
+      expect.objectContaining({ message: 'Wave at me' }),
+    );
+    expect(invoke).not.toHaveBeenCalledWith('chat_with_ethereal_stream', expect.anything());
+  });
+
+  it('cancels a pending reply when a new message replaces it', async () => {
+    let clipboardCallback: ((event: any) => void) | undefined;
+    const pending: ((reply: string) => void)[] = [];
+
+    (listen as Mock).mockImplementation(async (event, callback) => {
+      if (event === 'clipboard-changed') clipboardCallback = callback;
+      return () => {};
+    });
+    (invoke as Mock).mockImplementation((cmd) => {
+      if (cmd === 'chat_with_ethereal_stream') {
+        return new Promise((resolve) => pending.push(resolve));
+      }
+      return Promise.resolve(null);
+    });
+
+    render(<App />);
+    await waitFor(() => expect(clipboardCallback).toBeDefined());
+
+    let first: Promise<void> | undefined;
+    act(() => {
+      first = clipboardCallback?.({ payload: 'First' });
+    });
+    await waitFor(() => expect(pending).toHaveLength(1));
+    const firstId = (invoke as Mock).mock.calls.find(
+      ([cmd]) => cmd === 'chat_with_ethereal_stream',
+    )?.[1].requestId;
+    expect(firstId).toEqual(expect.any(String));
+
+    let second: Promise<void> | undefined;
+    act(() => {
+      second = clipboardCallback?.({ payload: 'Second' });
+    });
+    await waitFor(() => expect(pending).toHaveLength(2));
+    expect(invoke).toHaveBeenCalledWith('cancel_chat', { requestId: firstId });
+
+    await act(async () => {
+      pending[1]?.('Second reply');
+      await second;
+      pending[0]?.('First reply');
+      await first;
+    });
+
+    expect(useChatStore.getState().message).toBe('Second reply');
+  });
 });
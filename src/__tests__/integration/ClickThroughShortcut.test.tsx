@@ -52,7 +52,8 @@ describe('Click-through Shortcut Integration', () => {
     hardware: {
       monitor_source: 'auto',
       polling_interval_ms: 2000,
-      thresholds: { nvidia_temp: 80, amd_temp: 80, cpu_temp: 85 },
+      thresholds: { nvidia_temp: 80, amd_temp: 80, cpu_temp: 85, battery_temp: 50 },
+      cpu_sensor_labels: ['cpu', 'package', 'core'],
     },
     ai: {
       model_name: 'llama3.2',
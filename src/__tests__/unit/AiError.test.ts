@@ -0,0 +1,34 @@
+import { describe, expect, it } from 'vitest';
+import { aiErrorReply, isAiError } from '../../lib/aiError';
+
+describe('aiErrorReply', () => {
+  const fallback = 'fallback';
+
+  it('falls back for non-AI errors', () => {
+    expect(isAiError(new Error('boom'))).toBe(false);
+    expect(aiErrorReply(new Error('boom'), fallback)).toBe(fallback);
+    expect(aiErrorReply('plain string', fallback)).toBe(fallback);
+  });
+
+  it('sounds sleepy when the server is down', () => {
+    const reply = aiErrorReply(
+      { kind: 'connection_refused', endpoint: 'http://localhost:11434' },
+      fallback,
+    );
+    expect(reply).toContain('asleep');
+  });
+
+  it('names the missing model', () => {
+    expect(aiErrorReply({ kind: 'model_missing', model: 'llama3.2' }, fallback)).toContain(
+      'llama3.2',
+    );
+  });
+
+  it('reports the remaining cooldown', () => {
+    expect(aiErrorReply({ kind: 'cooldown', remaining_secs: 4 }, fallback)).toContain('4s');
+  });
+
+  it('falls back for generic server failures', () => {
+    expect(aiErrorReply({ kind: 'http', status: 500, body: 'boom' }, fallback)).toBe(fallback);
+  });
+});
@@ -0,0 +1,72 @@
+import { invoke } from '@tauri-apps/api/core';
+import { act } from '@testing-library/react';
+import { beforeEach, describe, expect, it, vi } from 'vitest';
+import { useChatStore } from '../../stores/chatStore';
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+}));
+
+describe('ChatStore', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    useChatStore.setState({
+      message: null,
+      isThinking: false,
+      isStreaming: false,
+      requestId: null,
+      isVisible: false,
+      offer: null,
+    });
+  });
+
+  it('appends streamed tokens after the first one replaces the thinking dots', () => {
+    act(() => {
+      useChatStore.getState().startRequest();
+      useChatStore.getState().appendToken('Hello');
+      useChatStore.getState().appendToken(', mortal.');
+    });
+
+    const state = useChatStore.getState();
+    expect(state.message).toBe('Hello, mortal.');
+    expect(state.isThinking).toBe(false);
+    expect(state.isStreaming).toBe(true);
+  });
+
+  it('cancels the pending request when a new one starts', () => {
+    let first = '';
+    let second = '';
+    act(() => {
+      first = useChatStore.getState().startRequest();
+      second = useChatStore.getState().startRequest();
+    });
+
+    expect(first).not.toBe(second);
+    expect(invoke).toHaveBeenCalledWith('cancel_chat', { requestId: first });
+    expect(useChatStore.getState().requestId).toBe(second);
+  });
+
+  it('cancels the pending request when the bubble is dismissed', () => {
+    let requestId = '';
+    act(() => {
+      requestId = useChatStore.getState().startRequest();
+      useChatStore.getState().dismiss();
+    });
+
+    expect(invoke).toHaveBeenCalledWith('cancel_chat', { requestId });
+    const state = useChatStore.getState();
+    expect(state.requestId).toBeNull();
+    expect(state.isVisible).toBe(false);
+    expect(state.isThinking).toBe(false);
+  });
+
+  it('does not cancel anything once the reply is shown', () => {
+    act(() => {
+      useChatStore.getState().startRequest();
+      useChatStore.getState().showResponse('Done.');
+      useChatStore.getState().dismiss();
+    });
+
+    expect(invoke).not.toHaveBeenCalledWith('cancel_chat', expect.anything());
+  });
+});
@@ -1,13 +1,60 @@
+import { invoke } from '@tauri-apps/api/core';
+import { useEffect, useState } from 'react';
 import type { SettingsTabProps } from './types';
 
+interface ModelInfo {
+  name: string;
+  size: number | null;
+  family: string | null;
+  parameter_size: string | null;
+  quantization: string | null;
+}
+
 export const AITab = ({ formData, setFormData }: SettingsTabProps) => {
+  const [models, setModels] = useState<ModelInfo[]>([]);
+
+  // Offer installed models as suggestions; free text still works when the
+  // server is unreachable.
+  useEffect(() => {
+    invoke<ModelInfo[]>('ai_list_models')
+      .then((list) => setModels(Array.isArray(list) ? list : []))
+      .catch(() => setModels([]));
+  }, []);
+
+  const personas = Object.entries(formData.ai.personas ?? {});
+
   return (
     <div className="space-y-4">
+      {personas.length > 0 && (
+        <div>
+          <label className="block text-sm font-medium mb-1">
+            Persona
+            <select
+              value={formData.ai.active_persona ?? ''}
+              onChange={(e) =>
+                setFormData({
+                  ...formData,
+                  ai: { ...formData.ai, active_persona: e.target.value || null },
+                })
+              }
+              className="w-full bg-white/5 border-white/10 rounded-xl p-3 text-sm border focus:ring-2 focus:ring-indigo-500/50 outline-none text-white transition-all"
+            >
+              <option value="">Default</option>
+              {personas.map(([id, persona]) => (
+                <option key={id} value={id}>
+                  {persona.name || id}
+                </option>
+              ))}
+            </select>
+          </label>
+        </div>
+      )}
       <div>
         <label className="block text-sm font-medium mb-1">
           Model Name
           <input
             type="text"
+            list="installed-models"
             value={formData.ai.model_name}
             onChange={(e) =>
               setFormData({
@@ -18,6 +65,13 @@ export const AITab = ({ formData, setFormData }: SettingsTabProps) => {
             className="w-full bg-white/5 border-white/10 rounded-xl p-3 text-sm border focus:ring-2 focus:ring-indigo-500/50 outline-none text-white transition-all"
             placeholder="llama3.2"
           />
+          <datalist id="installed-models">
+            {models.map((m) => (
+              <option key={m.name} value={m.name}>
+                {[m.family, m.parameter_size].filter(Boolean).join(' ')}
+              </option>
+            ))}
+          </datalist>
         </label>
       </div>
       <div>
@@ -72,6 +126,25 @@ export const AITab = ({ formData, setFormData }: SettingsTabProps) => {
             />
           </label>
         </div>
+        <div>
+          <label className="block text-sm font-medium mb-1">
+            Max Reply Length (chars)
+            <input
+              type="number"
+              value={formData.ai.max_response_length}
+              onChange={(e) =>
+                setFormData({
+                  ...formData,
+                  ai: {
+                    ...formData.ai,
+                    max_response_length: Number.parseInt(e.target.value, 10) || 0,
+                  },
+                })
+              }
+              className="w-full bg-white/5 border-white/10 rounded-xl p-3 text-sm border focus:ring-2 focus:ring-indigo-500/50 outline-none text-white transition-all"
+            />
+          </label>
+        </div>
         <div>
           <label className="block text-sm font-medium mb-1">
             Boredom Threshold (%)
@@ -1,8 +1,22 @@
+import { useState } from 'react';
 import { useSpriteStore } from '../../stores/spriteStore';
+import type { ThresholdsConfig } from '../../types/config';
 import type { SettingsTabProps } from './types';
 
+const THRESHOLDS: { key: keyof ThresholdsConfig; label: string }[] = [
+  { key: 'cpu_temp', label: 'CPU' },
+  { key: 'nvidia_temp', label: 'NVIDIA GPU' },
+  { key: 'amd_temp', label: 'AMD / Intel GPU' },
+  { key: 'battery_temp', label: 'Battery' },
+];
+
 export const HardwareTab = ({ formData, setFormData }: SettingsTabProps) => {
   const { hardware } = useSpriteStore();
+  // Kept as typed and split on blur, so a trailing comma survives the next
+  // keystroke.
+  const [sensorLabels, setSensorLabels] = useState(
+    formData.hardware.cpu_sensor_labels.join(', ')
+  );
 
   return (
     <div className="space-y-6">
@@ -53,6 +67,7 @@ export const HardwareTab = ({ formData, setFormData }: SettingsTabProps) => {
               <option value="nvidia">NVIDIA</option>
               <option value="amd">AMD</option>
               <option value="cpu">CPU</option>
+              <option value="composite">CPU + GPU</option>
             </select>
           </label>
         </div>
@@ -79,24 +94,50 @@ export const HardwareTab = ({ formData, setFormData }: SettingsTabProps) => {
           <h4 className="text-white/90 font-bold uppercase tracking-widest text-[10px] opacity-50">
             Thresholds
           </h4>
+          {THRESHOLDS.map(({ key, label }) => (
+            <div key={key}>
+              <label className="block text-sm font-medium mb-1">
+                {label} Temp Threshold (°C)
+                <input
+                  type="number"
+                  value={formData.hardware.thresholds[key]}
+                  onChange={(e) =>
+                    setFormData({
+                      ...formData,
+                      hardware: {
+                        ...formData.hardware,
+                        thresholds: {
+                          ...formData.hardware.thresholds,
+                          [key]: Number.parseFloat(e.target.value) || 0,
+                        },
+                      },
+                    })
+                  }
+                  className="w-full bg-white/5 border-white/10 rounded-xl p-3 text-sm border focus:ring-2 focus:ring-indigo-500/50 outline-none text-white transition-all"
+                />
+              </label>
+            </div>
+          ))}
           <div>
             <label className="block text-sm font-medium mb-1">
-              NVIDIA Temp Threshold (°C)
+              CPU Sensor Labels
               <input
-                type="number"
-                value={formData.hardware.thresholds.nvidia_temp}
-                onChange={(e) =>
+                type="text"
+                value={sensorLabels}
+                onChange={(e) => setSensorLabels(e.target.value)}
+                onBlur={() =>
                   setFormData({
                     ...formData,
                     hardware: {
                       ...formData.hardware,
-                      thresholds: {
-                        ...formData.hardware.thresholds,
-                        nvidia_temp: Number.parseFloat(e.target.value) || 0,
-                      },
+                      cpu_sensor_labels: sensorLabels
+                        .split(',')
+                        .map((label) => label.trim())
+                        .filter(Boolean),
                     },
                   })
                 }
+                placeholder="Package id 0, k10temp Tctl"
                 className="w-full bg-white/5 border-white/10 rounded-xl p-3 text-sm border focus:ring-2 focus:ring-indigo-500/50 outline-none text-white transition-all"
               />
             </label>
@@ -1,12 +1,23 @@
+import { aiErrorReply } from '@lib/aiError';
+import { logger } from '@lib/logger';
+import { invoke } from '@tauri-apps/api/core';
 import { AnimatePresence, motion } from 'framer-motion';
-import { useEffect, useState } from 'react';
+import { useEffect, useRef, useState } from 'react';
 import { useChatStore } from '../stores/chatStore';
 import { useSpriteStore } from '../stores/spriteStore';
+import type { ClipAction } from '../types/config';
+
+const ACTION_LABELS: Record<ClipAction, string> = {
+  explain: 'Explain',
+  summarize: 'Summarize',
+  translate: 'Translate',
+};
 
 export const SpeechBubble = () => {
-  const { message, isThinking, isVisible } = useChatStore();
+  const { message, isThinking, isVisible, offer, setThinking, showResponse } = useChatStore();
   const { mood } = useSpriteStore();
   const [displayedText, setDisplayedText] = useState('');
+  const typed = useRef('');
 
   const getMoodConfig = (m: string) => {
     switch (m) {
@@ -45,19 +56,36 @@ export const SpeechBubble = () => {
 
   const moodConfig = getMoodConfig(mood);
 
+  const runAction = async (action: ClipAction) => {
+    if (!offer) return;
+    setThinking(true);
+    try {
+      const result = await invoke<string>('run_clipboard_action', { action, text: offer.text });
+      showResponse(result);
+    } catch (e) {
+      logger.error('Clipboard action failed:', e);
+      showResponse(aiErrorReply(e, "I couldn't work that one out..."));
+    }
+  };
+
   useEffect(() => {
     if (!message || isThinking) {
+      typed.current = '';
       setDisplayedText('');
       return;
     }
 
-    let index = 0;
-    setDisplayedText('');
+    // A streamed reply grows one fragment at a time: keep typing from where
+    // we are instead of starting over.
+    if (!message.startsWith(typed.current)) {
+      typed.current = '';
+    }
+    setDisplayedText(typed.current);
 
     const interval = setInterval(() => {
-      if (index < message.length) {
-        setDisplayedText(message.slice(0, index + 1));
-        index++;
+      if (typed.current.length < message.length) {
+        typed.current = message.slice(0, typed.current.length + 1);
+        setDisplayedText(typed.current);
       } else {
         clearInterval(interval);
       }
@@ -149,6 +177,22 @@ export const SpeechBubble = () => {
                 )}
               </p>
             )}
+
+            {!isThinking && offer && offer.actions.length > 0 && (
+              <div className="flex gap-2 justify-center mt-3 pointer-events-auto">
+                {offer.actions.map((action) => (
+                  <button
+                    key={action}
+                    type="button"
+                    onClick={() => runAction(action)}
+                    className="px-3 py-1 rounded-full text-[12px] text-white/90 bg-white/10 hover:bg-white/20 transition-colors"
+                    style={{ border: `1px solid ${moodConfig.color}` }}
+                  >
+                    {ACTION_LABELS[action]}
+                  </button>
+                ))}
+              </div>
+            )}
           </div>
 
           {/* Enhanced tail with gradient */}
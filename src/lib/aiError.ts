@@ -0,0 +1,42 @@
+/**
+ * 后端 AI 命令返回的错误(见 docs/api.md)
+ */
+export type AiError =
+  | { kind: 'connection_refused'; endpoint: string }
+  | { kind: 'timeout' }
+  | { kind: 'http'; status: number; body: string }
+  | { kind: 'model_missing'; model: string }
+  | { kind: 'parse'; message: string }
+  | { kind: 'cooldown'; remaining_secs: number }
+  | { kind: 'cancelled' }
+  | { kind: 'other'; message: string };
+
+export function isAiError(error: unknown): error is AiError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as { kind?: unknown }).kind === 'string'
+  );
+}
+
+/**
+ * 根据错误类型生成精灵的回复,未知错误使用 fallback
+ */
+export function aiErrorReply(error: unknown, fallback: string): string {
+  if (!isAiError(error)) return fallback;
+
+  switch (error.kind) {
+    case 'connection_refused':
+      return 'Zzz... my brain is fast asleep. Is Ollama running?';
+    case 'timeout':
+      return 'Hmm... my thoughts are drifting too slowly today.';
+    case 'model_missing':
+      return `I can't find my mind "${error.model}". Pull it in the settings first.`;
+    case 'cooldown':
+      return `Let me catch my breath... ${error.remaining_secs}s.`;
+    case 'cancelled':
+      return 'Never mind, then.';
+    default:
+      return fallback;
+  }
+}
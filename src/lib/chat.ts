@@ -0,0 +1,55 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useChatStore } from '../stores/chatStore';
+import { useSettingsStore } from '../stores/settingsStore';
+
+/**
+ * 聊天命令的参数(见 docs/api.md)
+ */
+export interface ChatRequest {
+  message: string;
+  systemContext?: string;
+  mood?: string;
+  source?: string;
+}
+
+/**
+ * `chat-token` / `chat-done` 事件的负载
+ */
+interface ChatFragment {
+  request_id: string | null;
+  text: string;
+}
+
+/**
+ * 向精灵提问,回复边生成边显示在气泡里
+ * 开启动作(actions.enabled)时回复是 JSON,无法边收边显示,改用非流式命令
+ * 请求被取消或被新消息替换时返回 null,调用方不应再显示任何内容
+ */
+export async function askEthereal(request: ChatRequest): Promise<string | null> {
+  const store = useChatStore.getState();
+  const requestId = store.startRequest();
+  const isCurrent = () => useChatStore.getState().requestId === requestId;
+
+  try {
+    if (useSettingsStore.getState().config?.actions?.enabled) {
+      const reply = await invoke<string>('chat_with_ethereal', { ...request, requestId });
+      return isCurrent() ? reply : null;
+    }
+
+    const unlisten = await listen<ChatFragment>('chat-token', (event) => {
+      if (event.payload.request_id === requestId && isCurrent()) {
+        store.appendToken(event.payload.text);
+      }
+    });
+    try {
+      const reply = await invoke<string>('chat_with_ethereal_stream', { ...request, requestId });
+      return isCurrent() ? reply : null;
+    } finally {
+      unlisten();
+    }
+  } catch (e) {
+    if (!isCurrent()) return null;
+    throw e;
+  }
+}
@@ -1,4 +1,7 @@
+import { invoke } from '@tauri-apps/api/core';
 import { create } from 'zustand';
+import { logger } from '../lib/logger';
+import type { ClipboardOffer } from '../types/config';
 
 export interface ChatMessage {
   role: 'user' | 'assistant';
@@ -8,22 +11,47 @@ export interface ChatMessage {
 interface ChatState {
   message: string | null;
   isThinking: boolean;
+  /** A streamed reply is still arriving. */
+  isStreaming: boolean;
+  /** Id of the chat request the bubble is waiting on, if any. */
+  requestId: string | null;
   isVisible: boolean;
   history: ChatMessage[];
+  offer: ClipboardOffer | null;
 
   setMessage: (msg: string | null) => void;
   setThinking: (thinking: boolean) => void;
   setVisible: (visible: boolean) => void;
   showResponse: (msg: string) => void;
+  appendToken: (token: string) => void;
+  startRequest: () => string;
+  dismiss: () => void;
   addToHistory: (role: 'user' | 'assistant', content: string) => void;
   clearHistory: () => void;
+  showOffer: (msg: string, offer: ClipboardOffer) => void;
+  clearOffer: () => void;
 }
 
-export const useChatStore = create<ChatState>((set) => ({
+let requestCounter = 0;
+
+/** Abort a chat request the bubble no longer waits for. */
+const cancelRequest = async (requestId: string | null) => {
+  if (!requestId) return;
+  try {
+    await invoke('cancel_chat', { requestId });
+  } catch (e) {
+    logger.debug('Failed to cancel chat request:', e);
+  }
+};
+
+export const useChatStore = create<ChatState>((set, get) => ({
   message: null,
   isThinking: false,
+  isStreaming: false,
+  requestId: null,
   isVisible: false,
   history: [],
+  offer: null,
 
   setMessage: (message) => set({ message }),
   setThinking: (isThinking) => set({ isThinking }),
@@ -37,12 +65,56 @@ export const useChatStore = create<ChatState>((set) => ({
 
   clearHistory: () => set({ history: [] }),
 
+  showOffer: (message, offer) => {
+    cancelRequest(get().requestId);
+    set({ message, offer, isThinking: false, isStreaming: false, requestId: null, isVisible: true });
+    // Leave time to pick an action, unless something else replaced the offer
+    setTimeout(() => {
+      set((state) => (state.offer === offer ? { offer: null, isVisible: false } : {}));
+    }, 10000);
+  },
+
+  clearOffer: () => set({ offer: null }),
+
+  // The first fragment replaces the thinking dots; showResponse settles the final text.
+  appendToken: (token) =>
+    set((state) => ({
+      message: state.isStreaming ? `${state.message ?? ''}${token}` : token,
+      offer: null,
+      isThinking: false,
+      isStreaming: true,
+      isVisible: true,
+    })),
+
+  // A new request replaces whatever the bubble was waiting on.
+  startRequest: () => {
+    cancelRequest(get().requestId);
+    const requestId = `chat-${Date.now()}-${++requestCounter}`;
+    set({ requestId, isThinking: true, isStreaming: false, isVisible: true, offer: null });
+    return requestId;
+  },
+
+  dismiss: () => {
+    cancelRequest(get().requestId);
+    set({
+      message: null,
+      offer: null,
+      isThinking: false,
+      isStreaming: false,
+      requestId: null,
+      isVisible: false,
+    });
+  },
+
   showResponse: (message) => {
     set((state) => {
       const newHistory = [...state.history, { role: 'assistant' as const, content: message }];
       return {
         message,
+        offer: null,
         isThinking: false,
+        isStreaming: false,
+        requestId: null,
         isVisible: true,
         history: newHistory.slice(-10),
       };
@@ -15,15 +15,21 @@ export type SpriteState =
 export interface HardwareData {
   temperature: number;
   utilization: number;
+  cpu_utilization?: number | null;
+  gpu_utilization?: number | null;
   memory_used: number;
   memory_total: number;
+  vram?: [number, number] | null;
   network_rx: number;
   network_tx: number;
   disk_read: number;
   disk_write: number;
   battery_level: number;
   battery_state: string;
+  power_draw?: number | null;
+  overheating_sensor?: 'cpu' | 'nvidia_gpu' | 'amd_gpu' | 'intel_gpu' | 'battery' | null;
   active_window: string;
+  active_category: string;
   state: string;
   mood: string;
 }
@@ -13,20 +13,50 @@ export interface ThresholdsConfig {
   nvidia_temp: number;
   amd_temp: number;
   cpu_temp: number;
+  battery_temp: number;
 }
 
 export interface HardwareConfig {
   monitor_source: string;
   polling_interval_ms: number;
   thresholds: ThresholdsConfig;
+  cpu_sensor_labels: string[];
 }
 
 export interface AiConfig {
+  provider: 'ollama' | 'openai';
   model_name: string;
   api_endpoint: string;
   system_prompt: string;
   max_response_length: number;
   cooldown_seconds: number;
+  /** Comes back masked; send the mask or an empty string to keep the stored key. */
+  api_key?: string;
+  context_budget_tokens: number;
+  keep_recent_messages: number;
+  enable_tools: boolean;
+  max_retries: number;
+  retry_base_delay_ms: number;
+  mood_profiles: Record<string, MoodProfile>;
+  user_template: string;
+  personas: Record<string, Persona>;
+  active_persona?: string | null;
+  offline_fallback: boolean;
+}
+
+export interface Persona {
+  name: string;
+  system_prompt: string;
+  model_name?: string | null;
+  mood_profiles: Record<string, MoodProfile>;
+  sprite_path?: string | null;
+}
+
+export interface MoodProfile {
+  prompt_modifier: string;
+  temperature?: number;
+  top_p?: number;
+  max_tokens?: number;
 }
 
 export interface SoundConfig {
@@ -55,6 +85,44 @@ export interface SleepConfig {
   end_time: string;
 }
 
+export interface CommentaryConfig {
+  enabled: boolean;
+  min_interval_minutes: number;
+  long_work_minutes: number;
+}
+
+export interface ClipboardConfig {
+  actions_enabled: boolean;
+  translate_to: string;
+  max_result_length: number;
+}
+
+export type ClipAction = 'explain' | 'summarize' | 'translate';
+
+/** Payload of the `clipboard-offer` event. */
+export interface ClipboardOffer {
+  text: string;
+  kind: 'stack_trace' | 'compiler_error' | 'url' | 'code' | 'prose';
+  language?: string | null;
+  actions: ClipAction[];
+}
+
+export interface KnowledgeConfig {
+  enabled: boolean;
+  directory?: string | null;
+  embedding_model: string;
+  endpoint?: string | null;
+  top_k: number;
+  min_score: number;
+  chunk_chars: number;
+}
+
+export interface ActionsConfig {
+  enabled: boolean;
+  allowed: string[];
+  mood_minutes: number;
+}
+
 export interface InteractionConfig {
   double_click_action: string;
   enable_hover_effects: boolean;
@@ -88,4 +156,51 @@ export interface AppConfig {
   battery: BatteryConfig;
   autostart: AutostartConfig;
   privacy: PrivacyConfig;
+  commentary: CommentaryConfig;
+  clipboard: ClipboardConfig;
+  knowledge: KnowledgeConfig;
+  actions: ActionsConfig;
+}
+
+/** One chat request, as returned by `get_ai_stats`. */
+export interface RequestMetrics {
+  at: string;
+  provider: string;
+  model: string;
+  kind: 'chat' | 'stream' | 'tools';
+  ttfb_ms: number | null;
+  duration_ms: number;
+  prompt_tokens: number | null;
+  eval_tokens: number | null;
+  eval_ms: number | null;
+  error: string | null;
+}
+
+export interface ModelSummary {
+  model: string;
+  requests: number;
+  errors: number;
+  avg_ttfb_ms: number | null;
+  p50_duration_ms: number | null;
+  p95_duration_ms: number | null;
+  tokens_per_sec: number | null;
+}
+
+export interface DailyStats {
+  date: string;
+  model: string;
+  requests: number;
+  errors: number;
+  total_duration_ms: number;
+  total_ttfb_ms: number;
+  ttfb_samples: number;
+  prompt_tokens: number;
+  eval_tokens: number;
+  eval_ms: number;
+}
+
+export interface AiStats {
+  recent: RequestMetrics[];
+  models: ModelSummary[];
+  daily: DailyStats[];
 }